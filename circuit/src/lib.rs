@@ -264,12 +264,34 @@ pub struct Proof {
 	pub proof: Vec<u8>,
 }
 
-impl From<ProofRaw> for Proof {
-	fn from(value: ProofRaw) -> Self {
-		let pub_ins = value.pub_ins.iter().map(|x| Scalar::from_bytes(x).unwrap()).collect();
+impl Proof {
+	/// Raw Halo2 transcript bytes of the proof, as produced by `gen_proof`,
+	/// without the public inputs. Tools that supply the public inputs
+	/// separately can verify these bytes directly.
+	pub fn transcript_bytes(&self) -> &[u8] {
+		&self.proof
+	}
+}
+
+/// Error for a raw public input that is not a canonical field element
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidPublicInput {
+	/// Position of the public input
+	pub index: usize,
+}
+
+impl TryFrom<ProofRaw> for Proof {
+	type Error = InvalidPublicInput;
+
+	fn try_from(value: ProofRaw) -> Result<Self, Self::Error> {
+		let mut pub_ins = Vec::new();
+		for (index, x) in value.pub_ins.iter().enumerate() {
+			let pub_in: Option<Scalar> = Scalar::from_bytes(x).into();
+			pub_ins.push(pub_in.ok_or(InvalidPublicInput { index })?);
+		}
 		let proof = value.proof;
 
-		Self { pub_ins, proof }
+		Ok(Self { pub_ins, proof })
 	}
 }
 
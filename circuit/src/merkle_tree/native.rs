@@ -52,6 +52,11 @@ where
 		let root = nodes[&height][0].clone();
 		MerkleTree { nodes, height, root, _params: PhantomData }
 	}
+
+	/// Returns the root of the tree
+	pub fn root(&self) -> F {
+		self.root
+	}
 }
 
 #[derive(Clone)]
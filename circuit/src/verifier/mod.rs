@@ -133,6 +133,23 @@ pub fn evm_verify(deployment_code: Vec<u8>, instances: Vec<Vec<Fr>>, proof: Vec<
 	assert!(success);
 }
 
+/// Verify proof inside the smart contract, returning whether the verification
+/// passed instead of panicking
+pub fn try_evm_verify(deployment_code: Vec<u8>, instances: Vec<Vec<Fr>>, proof: Vec<u8>) -> bool {
+	let calldata = encode_calldata(&instances, &proof);
+	let mut evm = ExecutorBuilder::default().with_gas_limit(u64::MAX.into()).build();
+
+	let caller = Address::from_low_u64_be(0xfe);
+	let deployment_result = evm.deploy(caller, deployment_code.into(), 0.into());
+	let verifier_address = match deployment_result.address {
+		Some(address) => address,
+		None => return false,
+	};
+	let result = evm.call_raw(caller, verifier_address, calldata.into(), 0.into());
+
+	!result.reverted
+}
+
 #[cfg(test)]
 mod test {
 	use std::usize;
@@ -10,7 +10,7 @@ use eigen_trust_circuit::{
 	ProofRaw,
 };
 use eigen_trust_server::manager::{
-	attestation::{Attestation, AttestationData},
+	attestation::{resolve_abstentions, Attestation, AttestationData},
 	NUM_NEIGHBOURS,
 };
 use ethers::{
@@ -26,11 +26,13 @@ pub enum ClientError {
 	DecodeError,
 	ParseError,
 	TxError,
+	KeystoreError,
+	InvalidScores,
 }
 
 #[derive(Serialize, Deserialize, Debug, EthDisplay, Clone)]
 pub struct ClientConfig {
-	pub ops: [u128; NUM_NEIGHBOURS],
+	pub ops: [Option<u128>; NUM_NEIGHBOURS],
 	pub secret_key: [String; 2],
 	pub as_address: String,
 	pub et_verifier_wrapper_address: String,
@@ -85,14 +87,15 @@ impl EigenTrustClient {
 		let sk = SecretKey::from_raw([sk0, sk1]);
 		let pk = sk.public();
 
-		let ops = self.config.ops.map(|x| Scalar::from_u128(x));
+		let ops = resolve_abstentions(&self.config.ops).map_err(|_| ClientError::InvalidScores)?;
+		let ops: Vec<Scalar> = ops.into_iter().map(Scalar::from_u128).collect();
 
 		let (pks_hash, message_hash) =
-			calculate_message_hash::<NUM_NEIGHBOURS, 1>(user_publics.to_vec(), vec![ops.to_vec()]);
+			calculate_message_hash::<NUM_NEIGHBOURS, 1>(user_publics.to_vec(), vec![ops.clone()]);
 
 		let sig = sign(&sk, &pk, message_hash[0]);
 
-		let att = Attestation::new(sig, pk, user_publics.to_vec(), ops.to_vec());
+		let att = Attestation::new(sig, pk, user_publics.to_vec(), ops);
 		let att_data = AttestationData::from(att);
 		let bytes = att_data.to_bytes();
 
@@ -181,7 +184,7 @@ mod test {
 		let user_secrets_raw = vec![dummy_user; NUM_NEIGHBOURS];
 
 		let config = ClientConfig {
-			ops: [200, 200, 200, 200, 200],
+			ops: [Some(200); NUM_NEIGHBOURS],
 			secret_key: [
 				"2L9bbXNEayuRMMbrWFynPtgkrXH1iBdfryRH9Soa8M67".to_string(),
 				"9rBeBVtbN2MkHDTpeAouqkMWNFJC6Bxb6bXH9jUueWaF".to_string(),
@@ -219,7 +222,7 @@ mod test {
 		let user_secrets_raw = vec![dummy_user; NUM_NEIGHBOURS];
 
 		let config = ClientConfig {
-			ops: [200, 200, 200, 200, 200],
+			ops: [Some(200); NUM_NEIGHBOURS],
 			secret_key: [
 				"2L9bbXNEayuRMMbrWFynPtgkrXH1iBdfryRH9Soa8M67".to_string(),
 				"9rBeBVtbN2MkHDTpeAouqkMWNFJC6Bxb6bXH9jUueWaF".to_string(),
@@ -6,16 +6,17 @@ use eigen_trust_circuit::{
 use eigen_trust_client::{
 	utils::{
 		compile_sol_contract, compile_yul_contracts, deploy_as, deploy_et_wrapper, deploy_verifier,
-		read_csv_data,
+		read_csv_data, read_keystore, write_keystore,
 	},
-	ClientConfig, EigenTrustClient,
+	ClientConfig, ClientError, EigenTrustClient,
 };
+use eigen_trust_server::utils::generate_keypair;
 use ethers::{
 	abi::Address,
 	providers::Http,
 	signers::coins_bip39::{English, Mnemonic},
 };
-use std::str::FromStr;
+use std::{env, str::FromStr};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -29,11 +30,20 @@ enum Mode {
 	Show,
 	CompileContracts,
 	DeployContracts,
-	Attest,
+	Attest(AttestData),
+	CreateKeystore,
+	Keygen,
 	Update(UpdateData),
 	Verify,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Args)]
+struct AttestData {
+	/// Keystore file to read the secret key from, instead of the config
+	#[arg(long)]
+	keystore: Option<String>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Args)]
 struct UpdateData {
 	field: Option<String>,
@@ -67,6 +77,21 @@ async fn main() {
 	let user_secrets_raw: Vec<[String; 3]> = read_csv_data("bootstrap-nodes").unwrap();
 	let mut config: ClientConfig = read_json_data("client-config").unwrap();
 
+	// The decrypted key only replaces the one in memory, the config file is
+	// never written while attesting
+	if let Mode::Attest(AttestData { keystore: Some(path) }) = &cli.mode {
+		let password = rpassword::prompt_password("Keystore password: ");
+		let sk =
+			password.map_err(|_| ClientError::KeystoreError).and_then(|p| read_keystore(path, &p));
+		match sk {
+			Ok(sk) => config.secret_key = sk,
+			Err(e) => {
+				eprintln!("Failed to read the keystore: {:?}", e);
+				return;
+			},
+		}
+	}
+
 	let pos = user_secrets_raw.iter().position(|x| &config.secret_key == &x[1..]);
 	assert!(pos.is_some());
 
@@ -98,10 +123,32 @@ async fn main() {
 			let w_addr = wrapper_res.unwrap();
 			println!("EtVerifierWrapper contract deployed. Address: {}", w_addr);
 		},
-		Mode::Attest => {
+		Mode::Attest(_) => {
 			let client = EigenTrustClient::new(config, user_secrets_raw);
 			client.attest().await.unwrap();
 		},
+		Mode::CreateKeystore => {
+			let password = rpassword::prompt_password("New keystore password: ");
+			if password.is_err() {
+				eprintln!("Failed to read the password.");
+				return;
+			}
+			let keystore_dir = env::current_dir().unwrap().join("../data/keystore");
+			if let Err(e) = std::fs::create_dir_all(&keystore_dir) {
+				eprintln!("Failed to create the keystore directory: {:?}", e);
+				return;
+			}
+			match write_keystore(&keystore_dir, &config.secret_key, &password.unwrap()) {
+				Ok(name) => println!("Keystore created: data/keystore/{}", name),
+				Err(e) => eprintln!("Failed to create the keystore: {:?}", e),
+			}
+		},
+		Mode::Keygen => {
+			let (_, _, sk_bs58, pk_bs58) = generate_keypair();
+			println!("Secret key: {},{}", sk_bs58[0], sk_bs58[1]);
+			println!("Public key: {}", pk_bs58);
+			println!("Keep the secret key private. It can be set with `update sk <secret key>`.");
+		},
 		Mode::Verify => {
 			let url = format!("{}/score", config.server_url);
 			let proof_raw: ProofRaw = reqwest::get(url).await.unwrap().json().await.unwrap();
@@ -158,13 +205,17 @@ fn config_update(
 			let input: Vec<String> = data.split(" ").map(|x| x.to_string()).collect();
 
 			if input.len() != 2 {
-				return Err("Invalid input format. Expected: \"Alice 100\"".to_string());
+				return Err(
+					"Invalid input format. Expected: \"Alice 100\" or \"Alice abstain\""
+						.to_string(),
+				);
 			}
 
 			let name = input[0].clone();
 			let score = input[1].clone();
 
-			let score_parsed: Result<u128, _> = score.parse();
+			let score_parsed: Result<Option<u128>, _> =
+				if score == "abstain" { Ok(None) } else { score.parse().map(Some) };
 			if score_parsed.is_err() {
 				return Err("Failed to parse score.".to_string());
 			}
@@ -1,3 +1,4 @@
+use crate::ClientError;
 use csv::Reader as CsvReader;
 use eigen_trust_circuit::{
 	halo2::halo2curves::bn256::Fr as Scalar,
@@ -5,6 +6,7 @@ use eigen_trust_circuit::{
 	verifier::{compile_yul, encode_calldata},
 	Proof as NativeProof,
 };
+use eth_keystore::{decrypt_key, encrypt_key};
 use ethers::{
 	abi::Address,
 	middleware::SignerMiddleware,
@@ -14,6 +16,7 @@ use ethers::{
 	solc::{artifacts::ContractBytecode, Solc},
 	types::TransactionRequest,
 };
+use rand::thread_rng;
 use serde::de::DeserializeOwned;
 use std::{
 	env,
@@ -52,6 +55,38 @@ pub fn read_csv_data<T: DeserializeOwned>(file_name: &str) -> Result<Vec<T>, Err
 	Ok(records)
 }
 
+/// Encrypts the secret key, given as its two bs58 encoded parts, into a
+/// password protected keystore file (scrypt + AES-128-CTR) inside `dir`.
+/// Returns the name of the created file.
+pub fn write_keystore(
+	dir: impl AsRef<Path>, secret_key: &[String; 2], password: &str,
+) -> Result<String, ClientError> {
+	let mut raw = Vec::new();
+	for part in secret_key {
+		let bytes = bs58::decode(part).into_vec().map_err(|_| ClientError::DecodeError)?;
+		if bytes.len() != 32 {
+			return Err(ClientError::DecodeError);
+		}
+		raw.extend(bytes);
+	}
+
+	let mut rng = thread_rng();
+	encrypt_key(dir, &mut rng, raw, password, None).map_err(|_| ClientError::KeystoreError)
+}
+
+/// Decrypts the secret key from a keystore file created with
+/// `write_keystore`, returning its two bs58 encoded parts. The key is only
+/// kept in memory.
+pub fn read_keystore(path: impl AsRef<Path>, password: &str) -> Result<[String; 2], ClientError> {
+	let raw = decrypt_key(path, password).map_err(|_| ClientError::KeystoreError)?;
+	if raw.len() != 64 {
+		return Err(ClientError::DecodeError);
+	}
+	let sk0 = bs58::encode(&raw[..32]).into_string();
+	let sk1 = bs58::encode(&raw[32..]).into_string();
+	Ok([sk0, sk1])
+}
+
 abigen!(AttestationStation, "../data/AttestationStation.json");
 abigen!(EtVerifierWrapper, "../data/EtVerifierWrapper.json");
 pub type SignerMiddlewareArc = Arc<SignerMiddleware<Provider<Http>, LocalWallet>>;
@@ -159,12 +194,31 @@ pub fn compile_yul_contracts() {
 
 #[cfg(test)]
 mod test {
-	use super::{call_verifier, deploy_as, deploy_verifier};
+	use super::{call_verifier, deploy_as, deploy_verifier, read_keystore, write_keystore};
+	use crate::ClientError;
 	use eigen_trust_circuit::{
 		utils::{read_bytes_data, read_json_data},
 		Proof, ProofRaw,
 	};
 	use ethers::utils::Anvil;
+	use std::env;
+
+	#[test]
+	fn should_read_secret_key_from_keystore() {
+		let secret_key = [
+			"2L9bbXNEayuRMMbrWFynPtgkrXH1iBdfryRH9Soa8M67".to_string(),
+			"9rBeBVtbN2MkHDTpeAouqkMWNFJC6Bxb6bXH9jUueWaF".to_string(),
+		];
+		let dir = env::temp_dir();
+		let name = write_keystore(&dir, &secret_key, "password").unwrap();
+		let path = dir.join(name);
+
+		assert_eq!(read_keystore(&path, "password").unwrap(), secret_key);
+		let res = read_keystore(&path, "wrong password");
+		assert!(matches!(res, Err(ClientError::KeystoreError)));
+
+		std::fs::remove_file(path).unwrap();
+	}
 
 	#[tokio::test]
 	async fn should_deploy_the_as_contract() {
@@ -199,7 +253,7 @@ mod test {
 		let addr = deploy_verifier(mnemonic, &node_endpoint, bytecode).await.unwrap();
 
 		let proof_raw: ProofRaw = read_json_data("et_proof").unwrap();
-		let proof = Proof::from(proof_raw);
+		let proof = Proof::try_from(proof_raw).unwrap();
 		call_verifier(mnemonic, &node_endpoint, addr, proof).await;
 
 		drop(anvil);
@@ -1 +1,2 @@
-cargo test --all --release
\ No newline at end of file
+cargo test --all --release
+cargo test -p eigen-trust-server --release --features bench should_benchmark_convergence
\ No newline at end of file
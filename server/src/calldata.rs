@@ -0,0 +1,149 @@
+//! The module for the calldata of proofs submitted to verifier contracts, like:
+//! - Packing the public inputs and the transcript of a proof
+//! - Selecting the packing expected by a deployed verifier
+
+use crate::utils::scalar_to_word;
+use eigen_trust_circuit::Proof;
+
+/// Packing of a proof into the calldata of a verifier contract. The public
+/// inputs are always encoded as big-endian 32 byte words, like their `uint256`
+/// encoding on-chain.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CalldataEncoding {
+	/// The public inputs followed by the transcript, with no prefix, as the
+	/// generated Yul verifier reads them
+	#[default]
+	Canonical,
+	/// The number of public inputs as a word, followed by the canonical
+	/// calldata, for verifiers reading a variable number of public inputs
+	LengthPrefixed,
+	/// The ABI encoding of the `(uint256[] pubIns, bytes proof)` arguments,
+	/// without the function selector, for Solidity verifiers taking them as
+	/// dynamic arrays
+	Abi,
+}
+
+impl CalldataEncoding {
+	/// Look up the encoding by the name it is selected with
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"canonical" => Some(Self::Canonical),
+			"length-prefixed" => Some(Self::LengthPrefixed),
+			"abi" => Some(Self::Abi),
+			_ => None,
+		}
+	}
+
+	/// Pack the proof into calldata
+	pub fn encode(&self, proof: &Proof) -> Vec<u8> {
+		let pub_ins: Vec<u8> = proof.pub_ins.iter().flat_map(scalar_to_word).collect();
+		let transcript = proof.transcript_bytes();
+
+		let mut calldata = Vec::new();
+		match self {
+			Self::Canonical => {
+				calldata.extend(pub_ins);
+				calldata.extend_from_slice(transcript);
+			},
+			Self::LengthPrefixed => {
+				calldata.extend(usize_to_word(proof.pub_ins.len()));
+				calldata.extend(pub_ins);
+				calldata.extend_from_slice(transcript);
+			},
+			Self::Abi => {
+				// Offsets of the two arguments, from the start of the arguments
+				let pub_ins_offset = 2 * 32;
+				let proof_offset = pub_ins_offset + 32 + pub_ins.len();
+				calldata.extend(usize_to_word(pub_ins_offset));
+				calldata.extend(usize_to_word(proof_offset));
+
+				calldata.extend(usize_to_word(proof.pub_ins.len()));
+				calldata.extend(pub_ins);
+
+				calldata.extend(usize_to_word(transcript.len()));
+				calldata.extend_from_slice(transcript);
+				let padding = (32 - transcript.len() % 32) % 32;
+				calldata.extend(vec![0; padding]);
+			},
+		}
+		calldata
+	}
+}
+
+/// Encode a length or an offset as a big-endian 32 byte word
+fn usize_to_word(n: usize) -> [u8; 32] {
+	let mut word = [0u8; 32];
+	word[24..].copy_from_slice(&(n as u64).to_be_bytes());
+	word
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use eigen_trust_circuit::halo2::halo2curves::{bn256::Fr as Scalar, FieldExt};
+
+	fn proof() -> Proof {
+		let pub_ins = vec![Scalar::from_u128(1), Scalar::from_u128(0x0203)];
+		Proof { pub_ins, proof: vec![0xaa, 0xbb, 0xcc] }
+	}
+
+	/// Word holding the number in its last bytes
+	fn word(n: u16) -> Vec<u8> {
+		let mut word = vec![0; 30];
+		word.extend(n.to_be_bytes());
+		word
+	}
+
+	#[test]
+	fn should_encode_canonical_calldata() {
+		let calldata = CalldataEncoding::Canonical.encode(&proof());
+		let expected = [word(1), word(0x0203), vec![0xaa, 0xbb, 0xcc]].concat();
+		assert_eq!(calldata, expected);
+		assert_eq!(CalldataEncoding::default(), CalldataEncoding::Canonical);
+	}
+
+	#[test]
+	fn should_encode_length_prefixed_calldata() {
+		let calldata = CalldataEncoding::LengthPrefixed.encode(&proof());
+		let expected = [word(2), word(1), word(0x0203), vec![0xaa, 0xbb, 0xcc]].concat();
+		assert_eq!(calldata, expected);
+	}
+
+	#[test]
+	fn should_encode_abi_calldata() {
+		let calldata = CalldataEncoding::Abi.encode(&proof());
+		let mut padded_transcript = vec![0xaa, 0xbb, 0xcc];
+		padded_transcript.resize(32, 0);
+		let expected = [
+			// Offsets of the public inputs and the proof
+			word(0x40),
+			word(0xa0),
+			// Public inputs
+			word(2),
+			word(1),
+			word(0x0203),
+			// Proof, padded to a whole word
+			word(3),
+			padded_transcript,
+		]
+		.concat();
+		assert_eq!(calldata, expected);
+	}
+
+	#[test]
+	fn should_look_up_encoding_by_name() {
+		assert_eq!(
+			CalldataEncoding::from_name("canonical"),
+			Some(CalldataEncoding::Canonical)
+		);
+		assert_eq!(
+			CalldataEncoding::from_name("length-prefixed"),
+			Some(CalldataEncoding::LengthPrefixed)
+		);
+		assert_eq!(
+			CalldataEncoding::from_name("abi"),
+			Some(CalldataEncoding::Abi)
+		);
+		assert_eq!(CalldataEncoding::from_name("solana"), None);
+	}
+}
@@ -0,0 +1,197 @@
+//! The module for a typed client of the server routes, like:
+//! - Encoding the queries and the submitted attestations
+//! - Retrying the requests that fail transiently
+
+use crate::{
+	epoch::Epoch,
+	error::EigenError,
+	manager::attestation::{Attestation, AttestationData},
+	utils::public_key_to_bs58,
+};
+use eigen_trust_circuit::{
+	eddsa::native::PublicKey,
+	halo2::halo2curves::{bn256::Fr as Scalar, FieldExt},
+	Proof, ProofRaw,
+};
+use hyper::{body::to_bytes, client::HttpConnector, Body, Method, Request, StatusCode, Uri};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// Default number of retries of a transient failure
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+/// Delay before the first retry, doubled for each of the next ones
+const RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// Scores of `GET /scores`, in the order of the queried public keys
+#[derive(Deserialize)]
+struct OrderedScores {
+	scores: Vec<Option<String>>,
+}
+
+/// Result of a record submitted to `POST /attestations`
+#[derive(Deserialize)]
+struct BatchResult {
+	error: Option<EigenError>,
+}
+
+/// Client of the server routes, returning the typed errors the server
+/// answers with. Connections are pooled and kept alive between requests.
+/// Requests failing to connect, or answered with `503`, are retried with an
+/// exponential backoff. Submissions are retried too, since an attestation
+/// submitted again replaces the one before.
+pub struct Client {
+	inner: hyper::Client<HttpConnector>,
+	base_url: String,
+	max_retries: u32,
+}
+
+impl Client {
+	/// Construct a client for the server at the base URL, like
+	/// `http://localhost:3000`
+	pub fn new(base_url: &str) -> Self {
+		let base_url = base_url.trim_end_matches('/').to_string();
+		Self { inner: hyper::Client::new(), base_url, max_retries: DEFAULT_MAX_RETRIES }
+	}
+
+	/// Set the number of retries of a transient failure, `0` to never retry
+	pub fn set_max_retries(&mut self, max_retries: u32) {
+		self.max_retries = max_retries;
+	}
+
+	/// Query the score of the participant for the epoch. Fails with
+	/// `ParticipantNotFound` if the proof of the epoch has no score for it.
+	pub async fn get_score(&self, pk: &PublicKey, epoch: Epoch) -> Result<Scalar, EigenError> {
+		let path = format!("/scores?pks={}&epoch={}", public_key_to_bs58(pk), epoch.0);
+		let res: OrderedScores = self.send(Method::GET, &path, None).await?;
+		let score =
+			res.scores.into_iter().next().flatten().ok_or(EigenError::ParticipantNotFound)?;
+		let score = score.parse::<u128>().map_err(|_| EigenError::InvalidScore)?;
+		Ok(Scalar::from_u128(score))
+	}
+
+	/// Submit the attestation, failing with the reason it was rejected for
+	pub async fn submit_attestation(&self, att: Attestation) -> Result<(), EigenError> {
+		let record =
+			serde_json::to_string(&AttestationData::from(att)).map_err(|_| EigenError::Unknown)?;
+		let results: Vec<BatchResult> =
+			self.send(Method::POST, "/attestations", Some(record)).await?;
+		match results.into_iter().next() {
+			Some(BatchResult { error: Some(e) }) => Err(e),
+			Some(BatchResult { error: None }) => Ok(()),
+			None => Err(EigenError::Unknown),
+		}
+	}
+
+	/// Query the proof of the epoch
+	pub async fn get_proof(&self, epoch: Epoch) -> Result<Proof, EigenError> {
+		let path = format!("/score?epoch={}", epoch.0);
+		let proof: ProofRaw = self.send(Method::GET, &path, None).await?;
+		Proof::try_from(proof).map_err(|_| EigenError::InvalidScore)
+	}
+
+	/// Send the request and parse its JSON response, retrying transient
+	/// failures. Error responses are parsed into the error they carry, and
+	/// into `Unknown` if they don't carry one.
+	async fn send<T: DeserializeOwned>(
+		&self, method: Method, path: &str, body: Option<String>,
+	) -> Result<T, EigenError> {
+		let uri: Uri = format!("{}{}", self.base_url, path)
+			.parse()
+			.map_err(|_| EigenError::ConnectionError)?;
+
+		let mut retries = 0;
+		let res = loop {
+			let req = Request::builder()
+				.method(method.clone())
+				.uri(uri.clone())
+				.body(body.clone().map_or_else(Body::empty, Body::from))
+				.map_err(|_| EigenError::ConnectionError)?;
+			let res = self.inner.request(req).await;
+			let transient = match &res {
+				Ok(res) => res.status() == StatusCode::SERVICE_UNAVAILABLE,
+				Err(_) => true,
+			};
+			if !transient || retries >= self.max_retries {
+				break res.map_err(|_| EigenError::ConnectionError)?;
+			}
+			sleep(RETRY_DELAY * 2u32.pow(retries)).await;
+			retries += 1;
+		};
+
+		let status = res.status();
+		let bytes = to_bytes(res.into_body()).await.map_err(|_| EigenError::ConnectionError)?;
+		if !status.is_success() {
+			return Err(serde_json::from_slice(&bytes).unwrap_or(EigenError::Unknown));
+		}
+		serde_json::from_slice(&bytes).map_err(|_| EigenError::Unknown)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{manager::FIXED_SET, utils::keyset_from_raw};
+	use hyper::{
+		service::{make_service_fn, service_fn},
+		Response, Server,
+	};
+	use std::{
+		convert::Infallible,
+		sync::{
+			atomic::{AtomicUsize, Ordering},
+			Arc,
+		},
+	};
+	use tokio::spawn;
+
+	/// Serve `503` for the first `unavailable` requests, and the body after,
+	/// returning the URL of the server and the number of requests it got
+	fn serve(unavailable: usize, body: &'static str) -> (String, Arc<AtomicUsize>) {
+		let count = Arc::new(AtomicUsize::new(0));
+		let counter = count.clone();
+		let make_service = make_service_fn(move |_| {
+			let counter = counter.clone();
+			async move {
+				Ok::<_, Infallible>(service_fn(move |_req| {
+					let n = counter.fetch_add(1, Ordering::SeqCst);
+					let res = if n < unavailable {
+						Response::builder()
+							.status(503)
+							.body(Body::from("\"ConvergenceInProgress\""))
+					} else {
+						Response::builder().body(Body::from(body))
+					};
+					async move { Ok::<_, Infallible>(res.unwrap()) }
+				}))
+			}
+		});
+		let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+		let url = format!("http://{}", server.local_addr());
+		spawn(server);
+		(url, count)
+	}
+
+	#[tokio::test]
+	async fn should_retry_unavailable_server() {
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let (url, count) = serve(2, "{\"epoch\":3,\"scores\":[\"1000\"],\"found\":[true]}");
+
+		let client = Client::new(&url);
+		let score = client.get_score(&pks[0], Epoch(3)).await.unwrap();
+		assert_eq!(score, Scalar::from_u128(1000));
+		assert_eq!(count.load(Ordering::SeqCst), 3);
+	}
+
+	#[tokio::test]
+	async fn should_return_server_error() {
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let (url, count) = serve(usize::MAX, "");
+
+		let mut client = Client::new(&url);
+		client.set_max_retries(1);
+		let res = client.get_score(&pks[0], Epoch(3)).await;
+		assert_eq!(res, Err(EigenError::ConvergenceInProgress));
+		assert_eq!(count.load(Ordering::SeqCst), 2);
+	}
+}
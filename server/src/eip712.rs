@@ -0,0 +1,104 @@
+//! The module for the EIP-712 typed data of attestations, like:
+//! - Hashing the attestation fields under the EigenTrust domain
+//! - Recovering the Ethereum address that signed them
+
+use crate::{error::EigenError, manager::hash_public_key, utils::scalar_to_word};
+use eigen_trust_circuit::{eddsa::native::PublicKey, halo2::halo2curves::bn256::Fr as Scalar};
+use ethers::{
+	types::{Address, Signature, H256},
+	utils::keccak256,
+};
+
+/// Name of the signing domain
+pub const DOMAIN_NAME: &str = "EigenTrust";
+/// Version of the signing domain
+pub const DOMAIN_VERSION: &str = "1";
+/// Type of the signing domain
+const DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId)";
+/// Type of the attestation. Neighbours are identified by the hashes of their
+/// public keys, like the attestations are stored.
+const ATTESTATION_TYPE: &str = "Attestation(bytes32[] neighbours,uint256[] scores)";
+
+/// Hash an array of 32 byte words, as they are encoded inside a struct
+fn hash_words(words: impl Iterator<Item = [u8; 32]>) -> [u8; 32] {
+	let bytes: Vec<u8> = words.flatten().collect();
+	keccak256(bytes)
+}
+
+/// Hash of the signing domain for the given chain
+pub fn domain_separator(chain_id: u64) -> [u8; 32] {
+	let mut chain_word = [0u8; 32];
+	chain_word[24..].copy_from_slice(&chain_id.to_be_bytes());
+
+	let mut bytes = Vec::new();
+	bytes.extend_from_slice(&keccak256(DOMAIN_TYPE));
+	bytes.extend_from_slice(&keccak256(DOMAIN_NAME));
+	bytes.extend_from_slice(&keccak256(DOMAIN_VERSION));
+	bytes.extend_from_slice(&chain_word);
+	keccak256(bytes)
+}
+
+/// Hash of the attestation struct, over the neighbours and their scores
+pub fn attestation_struct_hash(neighbours: &[PublicKey], scores: &[Scalar]) -> [u8; 32] {
+	let pk_hashes = neighbours.iter().map(|pk| scalar_to_word(&hash_public_key(pk)));
+	let scores = scores.iter().map(scalar_to_word);
+
+	let mut bytes = Vec::new();
+	bytes.extend_from_slice(&keccak256(ATTESTATION_TYPE));
+	bytes.extend_from_slice(&hash_words(pk_hashes));
+	bytes.extend_from_slice(&hash_words(scores));
+	keccak256(bytes)
+}
+
+/// Digest signed by the wallet for the attestation, as defined by EIP-712
+pub fn attestation_digest(neighbours: &[PublicKey], scores: &[Scalar], chain_id: u64) -> [u8; 32] {
+	let mut bytes = vec![0x19, 0x01];
+	bytes.extend_from_slice(&domain_separator(chain_id));
+	bytes.extend_from_slice(&attestation_struct_hash(neighbours, scores));
+	keccak256(bytes)
+}
+
+/// Recover the address that signed the attestation from its 65 byte
+/// signature. A signature over other fields or another chain recovers some
+/// other address instead of failing, so the address has to be checked by the
+/// caller.
+pub fn recover_signer(
+	neighbours: &[PublicKey], scores: &[Scalar], chain_id: u64, sig: &[u8],
+) -> Result<Address, EigenError> {
+	let invalid =
+		|| EigenError::InvalidAttestation { reason: "invalid EIP-712 signature".to_string() };
+	let sig = Signature::try_from(sig).map_err(|_| invalid())?;
+	let digest = attestation_digest(neighbours, scores, chain_id);
+	sig.recover(H256::from(digest)).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{manager::FIXED_SET, utils::keyset_from_raw};
+	use eigen_trust_circuit::halo2::halo2curves::FieldExt;
+	use ethers::signers::{LocalWallet, Signer};
+	use rand::thread_rng;
+
+	#[test]
+	fn should_recover_signer() {
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let scores = vec![Scalar::from_u128(200); pks.len()];
+		let wallet = LocalWallet::new(&mut thread_rng());
+
+		let digest = attestation_digest(&pks, &scores, 1);
+		let sig = wallet.sign_hash(H256::from(digest)).to_vec();
+		let signer = recover_signer(&pks, &scores, 1, &sig).unwrap();
+		assert_eq!(signer, wallet.address());
+
+		// Signed for another chain, or over other scores
+		let signer = recover_signer(&pks, &scores, 5, &sig).unwrap();
+		assert_ne!(signer, wallet.address());
+		let mut other_scores = scores.clone();
+		other_scores[0] = Scalar::from_u128(201);
+		let signer = recover_signer(&pks, &other_scores, 1, &sig).unwrap();
+		assert_ne!(signer, wallet.address());
+
+		assert!(recover_signer(&pks, &scores, 1, &sig[..64]).is_err());
+	}
+}
@@ -4,6 +4,7 @@
 //! - Current epoch
 //! - Current timestamp
 
+use crate::error::EigenError;
 use std::{
 	fmt::{Display, Formatter, Result as FmtResult},
 	time::{SystemTime, UNIX_EPOCH},
@@ -32,28 +33,32 @@ impl Epoch {
 	}
 
 	/// Calculates the current epoch number based on the interval duration.
-	pub fn current_epoch(interval: u64) -> Self {
-		let secs = Self::current_timestamp();
+	/// Fails if the interval is zero.
+	pub fn current_epoch(interval: u64) -> Result<Self, EigenError> {
+		let secs = Self::current_timestamp()?;
 
-		let current_epoch = secs / interval;
+		let current_epoch = secs.checked_div(interval).ok_or(EigenError::InvalidEpochInterval)?;
 
-		Epoch(current_epoch)
+		Ok(Epoch(current_epoch))
 	}
 
 	/// Calculates the seconds until the next epoch based on the interval
-	/// duration.
-	pub fn secs_until_next_epoch(interval: u64) -> u64 {
-		let secs = Self::current_timestamp();
-		let current_epoch = Self::current_epoch(interval);
-		(current_epoch.0 + 1) * interval - secs
+	/// duration. Fails if the interval is zero.
+	pub fn secs_until_next_epoch(interval: u64) -> Result<u64, EigenError> {
+		let secs = Self::current_timestamp()?;
+		let current_epoch = Self::current_epoch(interval)?;
+		let next_epoch_start =
+			(current_epoch.0 + 1).checked_mul(interval).ok_or(EigenError::InvalidEpochInterval)?;
+		Ok(next_epoch_start - secs)
 	}
 
 	/// Calculates the current timestamp. The difference between UNIX timestamp
-	/// start and now.
-	pub fn current_timestamp() -> u64 {
-		let unix_timestamp =
-			SystemTime::now().duration_since(UNIX_EPOCH).expect("SystemTime Error - Unix time");
-		unix_timestamp.as_secs()
+	/// start and now. Fails if the system time is before the UNIX epoch.
+	pub fn current_timestamp() -> Result<u64, EigenError> {
+		let unix_timestamp = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map_err(|_| EigenError::SystemTimeError)?;
+		Ok(unix_timestamp.as_secs())
 	}
 
 	/// Returns previous epoch.
@@ -100,7 +105,7 @@ mod tests {
 	#[test]
 	fn epoch_current() {
 		let interval = 10;
-		let epoch = Epoch::current_epoch(interval);
+		let epoch = Epoch::current_epoch(interval).unwrap();
 
 		let unix_timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
 
@@ -112,7 +117,7 @@ mod tests {
 	#[test]
 	fn epoch_secs_until_next() {
 		let interval = 10;
-		let secs_until_next_epoch = Epoch::secs_until_next_epoch(interval);
+		let secs_until_next_epoch = Epoch::secs_until_next_epoch(interval).unwrap();
 
 		let unix_timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
 
@@ -124,10 +129,22 @@ mod tests {
 
 	#[test]
 	fn epoch_current_timestamp() {
-		let timestamp = Epoch::current_timestamp();
+		let timestamp = Epoch::current_timestamp().unwrap();
 
 		let unix_timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
 
 		assert_eq!(unix_timestamp.as_secs(), timestamp);
 	}
+
+	#[test]
+	fn epoch_zero_interval() {
+		assert_eq!(
+			Epoch::current_epoch(0),
+			Err(EigenError::InvalidEpochInterval)
+		);
+		assert_eq!(
+			Epoch::secs_until_next_epoch(0),
+			Err(EigenError::InvalidEpochInterval)
+		);
+	}
 }
@@ -1,8 +1,8 @@
-use serde::ser::StdError;
+use serde::{ser::StdError, Deserialize, Serialize};
 use std::fmt::{Display, Formatter, Result as FmtResult};
 
 /// The crate-wide error variants.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum EigenError {
 	/// Invalid pubkey of the bootstrap peer
 	InvalidBootstrapPubkey,
@@ -15,11 +15,61 @@ pub enum EigenError {
 	/// Failed to listen to requests
 	ListenError,
 	/// Attestation not found
-	AttestationNotFound,
+	AttestationNotFound {
+		/// Bs58 encoded public key of the participant
+		pk: String,
+	},
 	/// Attestation verification not passed
-	InvalidAttestation,
+	InvalidAttestation {
+		/// Which check the attestation failed
+		reason: String,
+	},
 	/// Proof not found
-	ProofNotFound,
+	ProofNotFound {
+		/// Epoch of the missing proof, `None` when no proof was calculated yet
+		epoch: Option<u64>,
+	},
+	/// Public key is not part of the participant set
+	ParticipantNotFound,
+	/// Fixed set secret keys don't match the public key hashes
+	InconsistentFixedSet,
+	/// Epoch interval must be greater than zero
+	InvalidEpochInterval,
+	/// System time is before the UNIX epoch
+	SystemTimeError,
+	/// Score is not a finite value in the [0, 1] range
+	InvalidScore,
+	/// Attestations are not accepted while a convergence is running
+	ConvergenceInProgress,
+	/// Trust graph is disconnected or periodic, so the scores don't converge
+	NotConverging,
+	/// Saved manager state can't be written or read back
+	InvalidState,
+	/// Manager was constructed without a proving key, so no proofs are made
+	ProvingDisabled,
+	/// Attestation is signed with a scheme that is not supported
+	UnsupportedSignatureScheme,
+	/// No proof was calculated yet, since not every participant attested
+	IncompleteParticipation {
+		/// Number of participants with an attestation
+		submitted: usize,
+		/// Number of participants required for the convergence
+		required: usize,
+	},
+	/// Scores of the attestation don't sum up to `SCALE` in strict mode
+	InvalidScoreSum,
+	/// Public key appears more than once in the participant set
+	DuplicateParticipant {
+		/// Bs58 encoded public key of the participant
+		pk: String,
+	},
+	/// Server secret key from the environment is not a valid encoding
+	InvalidServerKey,
+	/// Cached proof failed the verification and was evicted
+	InvalidProof {
+		/// Epoch of the evicted proof
+		epoch: u64,
+	},
 	/// Unknown error.
 	Unknown,
 }
@@ -32,14 +82,31 @@ impl From<EigenError> for u8 {
 			EigenError::VerificationError => 2,
 			EigenError::ConnectionError => 3,
 			EigenError::ListenError => 4,
-			EigenError::AttestationNotFound => 5,
-			EigenError::ProofNotFound => 6,
-			EigenError::InvalidAttestation => 7,
+			EigenError::AttestationNotFound { .. } => 5,
+			EigenError::ProofNotFound { .. } => 6,
+			EigenError::InvalidAttestation { .. } => 7,
+			EigenError::ParticipantNotFound => 8,
+			EigenError::InconsistentFixedSet => 9,
+			EigenError::InvalidEpochInterval => 10,
+			EigenError::SystemTimeError => 11,
+			EigenError::InvalidScore => 12,
+			EigenError::ConvergenceInProgress => 13,
+			EigenError::NotConverging => 14,
+			EigenError::InvalidState => 15,
+			EigenError::ProvingDisabled => 16,
+			EigenError::UnsupportedSignatureScheme => 17,
+			EigenError::IncompleteParticipation { .. } => 19,
+			EigenError::InvalidScoreSum => 20,
+			EigenError::DuplicateParticipant { .. } => 21,
+			EigenError::InvalidServerKey => 22,
+			EigenError::InvalidProof { .. } => 23,
 			EigenError::Unknown => 255,
 		}
 	}
 }
 
+/// Only the kind of the error is kept, the context of the variants is left
+/// empty.
 impl From<u8> for EigenError {
 	fn from(err: u8) -> Self {
 		match err {
@@ -48,9 +115,24 @@ impl From<u8> for EigenError {
 			2 => EigenError::VerificationError,
 			3 => EigenError::ConnectionError,
 			4 => EigenError::ListenError,
-			5 => EigenError::AttestationNotFound,
-			6 => EigenError::ProofNotFound,
-			7 => EigenError::InvalidAttestation,
+			5 => EigenError::AttestationNotFound { pk: String::new() },
+			6 => EigenError::ProofNotFound { epoch: None },
+			7 => EigenError::InvalidAttestation { reason: String::new() },
+			8 => EigenError::ParticipantNotFound,
+			9 => EigenError::InconsistentFixedSet,
+			10 => EigenError::InvalidEpochInterval,
+			11 => EigenError::SystemTimeError,
+			12 => EigenError::InvalidScore,
+			13 => EigenError::ConvergenceInProgress,
+			14 => EigenError::NotConverging,
+			15 => EigenError::InvalidState,
+			16 => EigenError::ProvingDisabled,
+			17 => EigenError::UnsupportedSignatureScheme,
+			19 => EigenError::IncompleteParticipation { submitted: 0, required: 0 },
+			20 => EigenError::InvalidScoreSum,
+			21 => EigenError::DuplicateParticipant { pk: String::new() },
+			22 => EigenError::InvalidServerKey,
+			23 => EigenError::InvalidProof { epoch: 0 },
 			_ => EigenError::Unknown,
 		}
 	}
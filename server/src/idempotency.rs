@@ -0,0 +1,113 @@
+//! The module for the idempotency of submissions, like:
+//! - Storing the responses by their idempotency key
+//! - Expiring and evicting the stored responses
+
+use std::{
+	collections::HashMap,
+	time::{Duration, Instant},
+};
+
+/// Response stored for an idempotency key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredResponse {
+	/// Status code of the response
+	pub status: u16,
+	/// Body of the response
+	pub body: String,
+}
+
+/// Responses by idempotency key, so a retried request gets the original
+/// response instead of being processed again. Entries expire after the TTL,
+/// and the oldest one is dropped when the cache is full.
+pub struct IdempotencyCache {
+	entries: HashMap<String, (Instant, StoredResponse)>,
+	ttl: Duration,
+	capacity: usize,
+}
+
+impl IdempotencyCache {
+	/// Constructs an empty cache
+	pub fn new(ttl: Duration, capacity: usize) -> Self {
+		Self { entries: HashMap::new(), ttl, capacity }
+	}
+
+	/// Get the response stored for the key, unless it expired
+	pub fn get(&self, key: &str) -> Option<StoredResponse> {
+		let (created, response) = self.entries.get(key)?;
+		if created.elapsed() >= self.ttl {
+			return None;
+		}
+		Some(response.clone())
+	}
+
+	/// Store the response for the key, dropping the expired entries first and
+	/// then the oldest one if the cache is still full
+	pub fn insert(&mut self, key: String, response: StoredResponse) {
+		let now = Instant::now();
+		let ttl = self.ttl;
+		self.entries.retain(|_, (created, _)| now.duration_since(*created) < ttl);
+
+		if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+			let oldest = self.entries.iter().min_by_key(|(_, (created, _))| *created);
+			if let Some(oldest_key) = oldest.map(|(key, _)| key.clone()) {
+				self.entries.remove(&oldest_key);
+			}
+		}
+		if self.capacity > 0 {
+			self.entries.insert(key, (now, response));
+		}
+	}
+
+	/// Number of stored responses, including the expired ones not dropped yet
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	/// Check if there are no stored responses
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn response(body: &str) -> StoredResponse {
+		StoredResponse { status: 200, body: body.to_string() }
+	}
+
+	#[test]
+	fn should_return_stored_response() {
+		let mut cache = IdempotencyCache::new(Duration::from_secs(60), 10);
+		assert_eq!(cache.get("key"), None);
+
+		cache.insert("key".to_string(), response("first"));
+		assert_eq!(cache.get("key"), Some(response("first")));
+		assert_eq!(cache.get("other"), None);
+	}
+
+	#[test]
+	fn should_expire_responses() {
+		let mut cache = IdempotencyCache::new(Duration::ZERO, 10);
+		cache.insert("key".to_string(), response("first"));
+		assert_eq!(cache.get("key"), None);
+
+		// Expired entries are dropped on the next insert
+		cache.insert("other".to_string(), response("second"));
+		assert_eq!(cache.len(), 1);
+	}
+
+	#[test]
+	fn should_evict_oldest_response() {
+		let mut cache = IdempotencyCache::new(Duration::from_secs(60), 2);
+		cache.insert("a".to_string(), response("a"));
+		cache.insert("b".to_string(), response("b"));
+		cache.insert("c".to_string(), response("c"));
+
+		assert_eq!(cache.len(), 2);
+		assert_eq!(cache.get("a"), None);
+		assert_eq!(cache.get("b"), Some(response("b")));
+		assert_eq!(cache.get("c"), Some(response("c")));
+	}
+}
@@ -29,6 +29,13 @@
 #![warn(trivial_casts)]
 #![forbid(unsafe_code)]
 
+/// The module for packing proofs into the calldata of verifier contracts
+pub mod calldata;
+/// The module for a typed client of the server routes
+pub mod client;
+/// The module for the EIP-712 typed data of attestations signed with Ethereum
+/// wallets
+pub mod eip712;
 /// The module for epoch-related calculations, like seconds until the next
 /// epoch, current epoch, etc.
 pub mod epoch;
@@ -36,10 +43,14 @@ pub mod epoch;
 pub mod error;
 /// Helper functions and ABIs for ethereum
 pub mod ethereum;
+/// The module for replaying the responses of retried submissions
+pub mod idempotency;
 /// The module for the manager related functionalities, like:
 /// - Adding/removing neighbors of peers
 /// - Calculating the score of peers
 /// - Keeping track of neighbors scores towards us
 pub mod manager;
+/// The module for the scores served to clients
+pub mod score;
 /// Common utility functions used across the crate
 pub mod utils;
@@ -1,38 +1,68 @@
-#![feature(async_closure)]
-
 use ethers::{
 	contract::EthEvent,
 	providers::StreamExt,
 	types::{Address, Filter, ValueOrArray},
 };
-use hyper::{server::conn::Http, service::service_fn, Body, Method, Request, Response};
-use once_cell::sync::Lazy;
-use rand::thread_rng;
-use serde::Deserialize;
-use serde_json::to_string;
+use flate2::{write::GzEncoder, Compression};
+use hyper::{
+	body::HttpBody,
+	header::{
+		HeaderValue, ACCEPT, ACCEPT_ENCODING, ALLOW, CONTENT_ENCODING, CONTENT_LENGTH,
+		CONTENT_TYPE, RETRY_AFTER, VARY,
+	},
+	server::conn::Http,
+	service::service_fn,
+	Body, Method, Request, Response, Uri,
+};
+use once_cell::sync::{Lazy, OnceCell};
+use rand::{thread_rng, Rng};
+use rayon::ThreadPoolBuilder;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{to_string, Deserializer};
 use std::{
+	collections::HashMap,
+	io::Write,
 	mem::drop,
 	net::SocketAddr,
-	sync::{Arc, Mutex},
+	num::ParseIntError,
+	sync::{
+		atomic::{AtomicBool, AtomicU64, Ordering},
+		Arc, Mutex,
+	},
+	thread::available_parallelism,
+	time::Instant,
 };
 use tokio::{
+	io::{AsyncRead, AsyncWrite},
 	net::TcpListener,
-	select,
+	select, spawn,
+	task::spawn_blocking,
 	time::{self, Duration},
 };
 
 use eigen_trust_circuit::{
+	calculate_message_hash,
 	circuit::EigenTrust,
-	utils::{keygen, read_json_data, read_params},
-	ProofRaw,
+	eddsa::native::{sign, PublicKey, SecretKey},
+	halo2::halo2curves::{bn256::Fr as Scalar, FieldExt},
+	utils::{field_to_string, keygen, read_json_data, read_params},
+	Proof, ProofRaw,
 };
 use eigen_trust_server::{
+	calldata::CalldataEncoding,
 	epoch::Epoch,
 	error::EigenError,
 	ethereum::{setup_client, AttestationCreatedFilter},
+	idempotency::{IdempotencyCache, StoredResponse},
 	manager::{
-		attestation::{Attestation, AttestationData},
-		Manager, INITIAL_SCORE, NUM_ITER, NUM_NEIGHBOURS, SCALE,
+		attestation::{Attestation, AttestationData, SignatureScheme},
+		hash_public_key, notification_message_hash, score_message_hash, ConvergenceProgress,
+		Manager, RankChange, FIXED_SET, INITIAL_SCORE, NUM_ITER, NUM_NEIGHBOURS, SCALE,
+	},
+	score::Score,
+	utils::{
+		bytes_from_hex, bytes_to_hex, keyset_from_raw, public_key_from_str, public_key_to_bs58,
+		scalar_to_hex, secret_key_from_bs58,
 	},
 };
 
@@ -42,32 +72,390 @@ struct ProtocolConfig {
 	endpoint: ([u8; 4], u16),
 	ethereum_node_url: String,
 	as_contract_address: String,
+	#[serde(default = "default_keep_alive")]
+	keep_alive: bool,
+	#[serde(default)]
+	reject_attestations_during_convergence: bool,
+	#[serde(default)]
+	proving_threads: Option<usize>,
+	#[serde(default)]
+	verify_proofs_before_serving: bool,
+	#[serde(default)]
+	max_cached_epochs: Option<usize>,
+	#[serde(default)]
+	webhook_url: Option<String>,
+	#[serde(default)]
+	score_floor: f64,
+	#[serde(default)]
+	native_only: bool,
+	#[serde(default)]
+	strict_score_sum: bool,
+}
+
+fn default_keep_alive() -> bool {
+	true
 }
 
 const BAD_REQUEST: u16 = 400;
+const FORBIDDEN: u16 = 403;
 const NOT_FOUND: u16 = 404;
+const METHOD_NOT_ALLOWED: u16 = 405;
+const REQUEST_TIMEOUT: u16 = 408;
+const PAYLOAD_TOO_LARGE: u16 = 413;
 const INTERNAL_SERVER_ERROR: u16 = 500;
+const NOT_IMPLEMENTED: u16 = 501;
+const SERVICE_UNAVAILABLE: u16 = 503;
+
+/// Header carrying the epoch the served score/proof was computed for
+const EPOCH_HEADER: &str = "X-Eigen-Epoch";
+/// Routes serving proofs, which are compressed for clients accepting gzip
+const COMPRESSED_ROUTES: [&str; 3] = ["/score", "/score/transcript", "/score/calldata"];
+/// Paths served by `handle_request`, with their method. Has to be kept in sync
+/// with its match arms, to answer requests with a known path but a different
+/// method with `405`, and to list the routes for unknown paths.
+const ROUTES: [(&str, &str); 26] = [
+	("/score", "GET"),
+	("/score/history", "GET"),
+	("/score/transcript", "GET"),
+	("/score/calldata", "GET"),
+	("/score/normalized", "GET"),
+	("/score/average", "GET"),
+	("/ranking-diff", "GET"),
+	("/scores", "GET"),
+	("/score/signed", "GET"),
+	("/status", "GET"),
+	("/epochs", "GET"),
+	("/validate", "GET"),
+	("/server-pubkey", "GET"),
+	("/attestation-root", "GET"),
+	("/is-member", "GET"),
+	("/pk-hash", "GET"),
+	("/attestation/message", "GET"),
+	("/metrics", "GET"),
+	("/coverage", "GET"),
+	("/verify-attestation", "POST"),
+	("/attestations", "POST"),
+	("/verify", "POST"),
+	("/attestation/replace", "POST"),
+	("/prove", "POST"),
+	("/score/distrust", "POST"),
+	("/reset", "POST"),
+];
+/// Largest attestation batch accepted, by its declared content length
+const MAX_BATCH_BYTES: u64 = 16 * 1024 * 1024;
+/// Largest single record in an attestation batch
+const MAX_RECORD_BYTES: usize = 64 * 1024;
+/// Largest proof accepted for verification
+const MAX_PROOF_BYTES: usize = 1024 * 1024;
+/// Largest set of attestations accepted for proving, one record per participant
+const MAX_PROVE_BYTES: usize = NUM_NEIGHBOURS * MAX_RECORD_BYTES;
+/// Time allowed for receiving a proof for verification
+const PROOF_READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Header carrying the id of a request, to find its lines in the logs
+const REQUEST_ID_HEADER: &str = "X-Request-ID";
+/// Longest request id taken from the client
+const MAX_REQUEST_ID_LEN: usize = 128;
+/// Header carrying the client chosen key of a retry-safe submission
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+/// Routes whose responses are replayed for a repeated idempotency key
+const IDEMPOTENT_ROUTES: [&str; 2] = ["/attestations", "/attestation/replace"];
+/// Time a response is kept for its idempotency key
+const IDEMPOTENCY_TTL: Duration = Duration::from_secs(10 * 60);
+/// Most responses kept for idempotency keys at once
+const IDEMPOTENCY_CAPACITY: usize = 10_000;
+/// Header carrying the token for the admin routes
+const ADMIN_TOKEN_HEADER: &str = "X-Admin-Token";
+/// Environment variable holding the admin token. Admin routes are disabled
+/// when it is not set.
+const ADMIN_TOKEN_VAR: &str = "EIGEN_ADMIN_TOKEN";
+/// Header carrying the server signature of a webhook notification
+const WEBHOOK_SIGNATURE_HEADER: &str = "X-Eigen-Signature";
+/// Number of retries of a failed webhook delivery
+const WEBHOOK_RETRIES: u32 = 3;
+/// Delay before the first retry of a webhook delivery, doubled for each of the
+/// next ones
+const WEBHOOK_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Serialize)]
+struct ScoreEntry {
+	epoch: u64,
+	score: String,
+}
+
+#[derive(Debug, Serialize)]
+struct NormalizedScore {
+	pk: String,
+	score: Score,
+}
+
+#[derive(Debug, Serialize)]
+struct RawScore {
+	pk: String,
+	score: String,
+}
+
+#[derive(Debug, Serialize)]
+struct AverageScore {
+	pk: String,
+	score: Score,
+	epochs: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct OrderedScores {
+	epoch: u64,
+	scores: Vec<Option<String>>,
+	found: Vec<bool>,
+}
+
+#[derive(Debug, Serialize)]
+struct SignedScore {
+	pk: String,
+	epoch: u64,
+	score: String,
+	sig_r_x: String,
+	sig_r_y: String,
+	sig_s: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ConvergenceNotification {
+	epoch: u64,
+	scores: Vec<NotifiedScore>,
+}
+
+#[derive(Debug, Serialize)]
+struct NotifiedScore {
+	pk: String,
+	score: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ConvergenceStatus {
+	converging: bool,
+	progress: Option<ConvergenceProgress>,
+	last_epoch: Option<u64>,
+	last_duration_ms: Option<u64>,
+	next_epoch_in_s: u64,
+	participation: Option<Participation>,
+	estimated_proving_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct Participation {
+	submitted: usize,
+	required: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct ValidationReport {
+	untrusted: Vec<String>,
+}
+
+/// Neighbour of an attestation, with the raw coordinates of its key
+#[derive(Debug, Serialize)]
+struct MessageNeighbour {
+	pk: String,
+	x: String,
+	y: String,
+}
+
+/// What an attestation signature is over, as the server interprets it
+#[derive(Debug, Serialize)]
+struct AttestationMessage {
+	neighbours: Vec<MessageNeighbour>,
+	scores: Vec<String>,
+	pks_hash: String,
+	message_hash: String,
+}
+
+/// Distrust of a participant for the distrust preview, with the key in either
+/// of its encodings
+#[derive(Debug, Deserialize)]
+struct DistrustEntry {
+	pk: String,
+	distrust: Vec<u128>,
+}
+
+#[derive(Debug, Serialize)]
+struct BatchResult {
+	index: usize,
+	error: Option<EigenError>,
+}
+
+/// A route served by `handle_request`, listed for unknown paths
+#[derive(Debug, Serialize)]
+struct RouteInfo {
+	path: &'static str,
+	method: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct ResetCounts {
+	attestations: usize,
+	proofs: usize,
+}
 
 #[derive(Debug)]
 enum ResponseBody {
 	Score(ProofRaw),
+	Proof(ProofRaw),
+	ProofValid(bool),
+	ScoreHistory(Vec<ScoreEntry>),
+	NormalizedScores(Vec<NormalizedScore>),
+	RawScores(Vec<RawScore>),
+	AverageScore(AverageScore),
+	RankingDiff(Vec<RankChange>),
+	OrderedScores(OrderedScores),
+	Transcript(String),
+	Calldata(String),
+	Reset(ResetCounts),
+	BatchResults(Vec<BatchResult>),
+	AttestationRoot(String),
+	Epochs(Vec<u64>),
+	Validation(ValidationReport),
+	IsMember(bool),
+	PkHash(String),
+	AttestationMessage(AttestationMessage),
+	SignatureValid(bool),
+	Replaced(bool),
+	SignedScore(SignedScore),
+	ServerPubkey(String),
+	Status(ConvergenceStatus),
+	Metrics(String),
+	Coverage(Vec<Vec<bool>>),
+	Routes(Vec<RouteInfo>),
 	LockError,
 	InvalidQuery,
 	InvalidRequest,
+	MethodNotAllowed,
+	Unauthorized,
+	ProvingError,
+	Error(EigenError),
+	PayloadTooLarge,
+	RequestTimeout,
+	IncompleteBody,
+	EmptyBody,
+	DeadlineExceeded,
+	FutureEpoch,
 }
 
 impl ToString for ResponseBody {
 	fn to_string(&self) -> String {
 		match self {
 			ResponseBody::Score(proof) => to_string(&proof).unwrap(),
+			ResponseBody::Proof(proof) => to_string(&proof).unwrap(),
+			ResponseBody::ProofValid(is_valid) => to_string(&is_valid).unwrap(),
+			ResponseBody::ScoreHistory(history) => to_string(&history).unwrap(),
+			ResponseBody::NormalizedScores(scores) => to_string(&scores).unwrap(),
+			ResponseBody::RawScores(scores) => to_string(&scores).unwrap(),
+			ResponseBody::AverageScore(score) => to_string(&score).unwrap(),
+			ResponseBody::RankingDiff(changes) => to_string(&changes).unwrap(),
+			ResponseBody::OrderedScores(scores) => to_string(&scores).unwrap(),
+			ResponseBody::Transcript(transcript) => to_string(&transcript).unwrap(),
+			ResponseBody::Calldata(calldata) => to_string(&calldata).unwrap(),
+			ResponseBody::Reset(counts) => to_string(&counts).unwrap(),
+			ResponseBody::BatchResults(results) => to_string(&results).unwrap(),
+			ResponseBody::AttestationRoot(root) => to_string(&root).unwrap(),
+			ResponseBody::Epochs(epochs) => to_string(&epochs).unwrap(),
+			ResponseBody::Validation(report) => to_string(&report).unwrap(),
+			ResponseBody::IsMember(is_member) => to_string(&is_member).unwrap(),
+			ResponseBody::PkHash(pk_hash) => to_string(&pk_hash).unwrap(),
+			ResponseBody::AttestationMessage(message) => to_string(&message).unwrap(),
+			ResponseBody::SignatureValid(is_valid) => to_string(&is_valid).unwrap(),
+			ResponseBody::Replaced(replaced) => to_string(&replaced).unwrap(),
+			ResponseBody::SignedScore(signed_score) => to_string(&signed_score).unwrap(),
+			ResponseBody::ServerPubkey(pk) => to_string(&pk).unwrap(),
+			ResponseBody::Status(status) => to_string(&status).unwrap(),
+			ResponseBody::Metrics(metrics) => metrics.clone(),
+			ResponseBody::Coverage(matrix) => to_string(&matrix).unwrap(),
+			ResponseBody::Routes(routes) => to_string(&routes).unwrap(),
 			ResponseBody::LockError => "LockError".to_string(),
 			ResponseBody::InvalidQuery => "InvalidQuery".to_string(),
 			ResponseBody::InvalidRequest => "InvalidRequest".to_string(),
+			ResponseBody::MethodNotAllowed => "MethodNotAllowed".to_string(),
+			ResponseBody::Unauthorized => "Unauthorized".to_string(),
+			ResponseBody::ProvingError => "ProvingError".to_string(),
+			ResponseBody::Error(e) => to_string(&e).unwrap(),
+			ResponseBody::PayloadTooLarge => "PayloadTooLarge".to_string(),
+			ResponseBody::RequestTimeout => "RequestTimeout".to_string(),
+			ResponseBody::IncompleteBody => "IncompleteBody".to_string(),
+			ResponseBody::EmptyBody => "EmptyBody".to_string(),
+			ResponseBody::DeadlineExceeded => "DeadlineExceeded".to_string(),
+			ResponseBody::FutureEpoch => "FutureEpoch".to_string(),
 		}
 	}
 }
 
+/// Environment variable holding the server secret key used to sign scores,
+/// as two comma separated bs58 strings. A random key is used when unset.
+const SERVER_KEY_VAR: &str = "EIGEN_SERVER_SECRET_KEY";
+
+/// Server secret key, set from the environment at startup
+static SERVER_KEY: OnceCell<SecretKey> = OnceCell::new();
+
+/// Read the server secret key from the environment, or generate a random one
+/// when it is not set
+fn read_server_key() -> Result<SecretKey, EigenError> {
+	match std::env::var(SERVER_KEY_VAR) {
+		Ok(raw) => secret_key_from_bs58(&raw).ok_or(EigenError::InvalidServerKey),
+		Err(_) => Ok(SecretKey::random(&mut thread_rng())),
+	}
+}
+
+/// Get the server secret key. Falls back to a random key if it was never set
+/// at startup, like in tests.
+fn server_key() -> &'static SecretKey {
+	SERVER_KEY.get_or_init(|| SecretKey::random(&mut thread_rng()))
+}
+
+/// Environment variable overriding the number of proving threads from the
+/// config
+const PROVING_THREADS_VAR: &str = "EIGEN_PROVING_THREADS";
+
+/// Environment variable overriding the total time a request may take, in
+/// milliseconds. `0` disables the deadline.
+const REQUEST_DEADLINE_VAR: &str = "EIGEN_REQUEST_DEADLINE_MS";
+/// Default total time a request may take
+const DEFAULT_REQUEST_DEADLINE: Duration = Duration::from_secs(60);
+/// Seconds a client is asked to wait before retrying a request that ran out
+/// of time
+const DEADLINE_RETRY_AFTER_SECS: u64 = 5;
+
+static REQUEST_DEADLINE: Lazy<Option<Duration>> =
+	Lazy::new(
+		|| match std::env::var(REQUEST_DEADLINE_VAR).ok().and_then(|x| x.parse().ok()) {
+			Some(0) => None,
+			Some(ms) => Some(Duration::from_millis(ms)),
+			None => Some(DEFAULT_REQUEST_DEADLINE),
+		},
+	);
+
+/// Set while a convergence is running, so overlapping ticks are skipped
+static CONVERGENCE_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+/// Last milestone reached by the running convergence
+static CONVERGENCE_PROGRESS: Mutex<Option<ConvergenceProgress>> = Mutex::new(None);
+/// Epoch and duration in milliseconds of the last finished convergence
+static LAST_CONVERGENCE: Mutex<Option<(Epoch, u64)>> = Mutex::new(None);
+/// Epoch interval from the config, in seconds
+static EPOCH_INTERVAL: AtomicU64 = AtomicU64::new(0);
+/// URL notified after each successful convergence, when configured
+static WEBHOOK_URL: OnceCell<Uri> = OnceCell::new();
+/// Set when attestations are rejected while a convergence is running
+static REJECT_DURING_CONVERGENCE: AtomicBool = AtomicBool::new(false);
+/// Set when cached proofs have to pass the verification before being served
+static VERIFY_BEFORE_SERVE: AtomicBool = AtomicBool::new(false);
+/// Set when the manager is created without a proving key, serving only the
+/// natively calculated scores
+static NATIVE_ONLY: AtomicBool = AtomicBool::new(false);
+/// Responses of the submissions, by their idempotency key
+static IDEMPOTENCY_CACHE: Lazy<Mutex<IdempotencyCache>> =
+	Lazy::new(|| Mutex::new(IdempotencyCache::new(IDEMPOTENCY_TTL, IDEMPOTENCY_CAPACITY)));
+
 static MANAGER_STORE: Lazy<Arc<Mutex<Manager>>> = Lazy::new(|| {
+	if NATIVE_ONLY.load(Ordering::SeqCst) {
+		return Arc::new(Mutex::new(Manager::new_native_only()));
+	}
 	let k = 14;
 	let params = read_params(k);
 	let rng = &mut thread_rng();
@@ -82,11 +470,436 @@ static MANAGER_STORE: Lazy<Arc<Mutex<Manager>>> = Lazy::new(|| {
 	Arc::new(Mutex::new(Manager::new(params, proving_key)))
 });
 
+/// Parse the query string of the uri into key-value pairs
+fn parse_query(uri: &Uri) -> HashMap<String, String> {
+	let query = uri.query().unwrap_or_default();
+	query
+		.split('&')
+		.filter_map(|pair| pair.split_once('='))
+		.map(|(key, value)| (key.to_string(), value.to_string()))
+		.collect()
+}
+
+/// Parse an optional epoch number from the query
+fn query_epoch(query: &HashMap<String, String>, key: &str) -> Result<Option<Epoch>, ParseIntError> {
+	query.get(key).map(|x| x.parse().map(Epoch)).transpose()
+}
+
+/// Check whether the epoch is after the current one. Always `false` while the
+/// epoch interval is not set.
+fn is_future_epoch(epoch: Epoch) -> bool {
+	let current = Epoch::current_epoch(EPOCH_INTERVAL.load(Ordering::SeqCst));
+	current.map_or(false, |current| epoch.0 > current.0)
+}
+
+/// Check that the request carries the admin token from the environment
+fn is_admin(req: &Request<Body>) -> bool {
+	let token = std::env::var(ADMIN_TOKEN_VAR);
+	let header = req.headers().get(ADMIN_TOKEN_HEADER);
+	match (token, header) {
+		(Ok(token), Some(header)) => !token.is_empty() && header == token.as_str(),
+		_ => false,
+	}
+}
+
+/// Get the proof of the epoch for serving, verifying it first if enabled
+fn serve_proof(manager: &mut Manager, epoch: Epoch) -> Result<Proof, EigenError> {
+	if VERIFY_BEFORE_SERVE.load(Ordering::SeqCst) {
+		manager.get_verified_proof(epoch).map_err(|e| {
+			if let EigenError::InvalidProof { .. } = e {
+				println!("Evicted the invalid proof of {}", epoch);
+			}
+			e
+		})
+	} else {
+		manager.get_proof(epoch)
+	}
+}
+
+/// Format the proof cache stats of the manager in the Prometheus text format.
+/// An unlimited capacity is reported as `+Inf`.
+fn format_metrics(manager: &Manager) -> String {
+	let (size, capacity, evictions) = manager.proof_cache_stats();
+	let capacity = capacity.map_or("+Inf".to_string(), |x| x.to_string());
+	let mut metrics = vec![
+		(
+			"eigen_proof_cache_size",
+			"gauge",
+			"Number of epochs with a cached proof",
+			size.to_string(),
+		),
+		(
+			"eigen_proof_cache_capacity",
+			"gauge",
+			"Maximum number of epochs with a cached proof",
+			capacity,
+		),
+		(
+			"eigen_proof_cache_evictions_total",
+			"counter",
+			"Proofs evicted to keep the cache within its capacity",
+			evictions.to_string(),
+		),
+	];
+	// Left out until there is a proof to measure
+	let entropy = manager.get_last_epoch().and_then(|epoch| manager.score_entropy(epoch));
+	if let Ok(entropy) = entropy {
+		metrics.push((
+			"eigen_score_entropy_bits",
+			"gauge",
+			"Shannon entropy of the scores of the last epoch, lower when trust is concentrated",
+			entropy.to_string(),
+		));
+	}
+	metrics
+		.iter()
+		.map(|(name, kind, help, value)| {
+			format!("# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n")
+		})
+		.collect()
+}
+
+/// Build the response for an error from the manager, with a status matching
+/// the kind of the error and the error with its context as the body
+fn error_response(e: EigenError) -> Response<String> {
+	println!("{:?}", e);
+	let status = match e {
+		EigenError::AttestationNotFound { .. }
+		| EigenError::ProofNotFound { .. }
+		| EigenError::ParticipantNotFound
+		| EigenError::IncompleteParticipation { .. } => NOT_FOUND,
+		EigenError::InvalidAttestation { .. }
+		| EigenError::InvalidScore
+		| EigenError::UnsupportedSignatureScheme
+		| EigenError::InvalidScoreSum => BAD_REQUEST,
+		EigenError::ConvergenceInProgress => SERVICE_UNAVAILABLE,
+		EigenError::ProvingDisabled => NOT_IMPLEMENTED,
+		_ => INTERNAL_SERVER_ERROR,
+	};
+	Response::builder().status(status).body(ResponseBody::Error(e).to_string()).unwrap()
+}
+
+/// Parse whitespace separated `AttestationData` records from the body as its
+/// chunks arrive, and add each of them to the manager. Only an incomplete
+/// record and the latest chunk are kept in memory. Parsing stops at the first
+/// malformed or oversized record, which is reported as the last result.
+async fn add_attestation_batch(
+	mut body: Body, arc_manager: &Arc<Mutex<Manager>>,
+) -> Result<Vec<BatchResult>, EigenError> {
+	let mut results = Vec::new();
+	let mut buffer = Vec::new();
+	while let Some(chunk) = body.data().await {
+		let chunk = chunk.map_err(|_| EigenError::ConnectionError)?;
+		buffer.extend_from_slice(&chunk);
+
+		let mut stream = Deserializer::from_slice(&buffer).into_iter::<AttestationData>();
+		let mut consumed = 0;
+		loop {
+			match stream.next() {
+				Some(Ok(att_data)) => {
+					consumed = stream.byte_offset();
+					let att = Attestation::from(att_data);
+					let error = arc_manager
+						.lock()
+						.map_err(|_| EigenError::Unknown)
+						.and_then(|mut manager| manager.add_attestation(att))
+						.err();
+					results.push(BatchResult { index: results.len(), error });
+				},
+				// The rest of the record is in the next chunks
+				Some(Err(e)) if e.is_eof() => break,
+				Some(Err(_)) => {
+					let reason = "malformed record".to_string();
+					let error = Some(EigenError::InvalidAttestation { reason });
+					results.push(BatchResult { index: results.len(), error });
+					return Ok(results);
+				},
+				None => {
+					consumed = stream.byte_offset();
+					break;
+				},
+			}
+		}
+		drop(stream);
+		buffer.drain(..consumed);
+
+		if buffer.len() > MAX_RECORD_BYTES {
+			let reason = "record too large".to_string();
+			let error = Some(EigenError::InvalidAttestation { reason });
+			results.push(BatchResult { index: results.len(), error });
+			return Ok(results);
+		}
+	}
+
+	if !buffer.iter().all(u8::is_ascii_whitespace) {
+		let reason = "incomplete record".to_string();
+		let error = Some(EigenError::InvalidAttestation { reason });
+		results.push(BatchResult { index: results.len(), error });
+	}
+
+	Ok(results)
+}
+
+/// Check if the request asks for lenient parsing of its body, with
+/// `?lenient=true`
+fn is_lenient(req: &Request<Body>) -> bool {
+	parse_query(req.uri()).get("lenient").map_or(false, |v| v == "true")
+}
+
+/// Read the whole body, up to `limit` bytes, and parse it as JSON, or as JSON5
+/// when `lenient` is set, which allows comments and trailing commas. A body
+/// that doesn't arrive completely, like when the client disconnects
+/// mid-upload, is answered with `408` and `IncompleteBody`, one over the limit
+/// with `413` and `PayloadTooLarge`, one with nothing but whitespace with `400`
+/// and `EmptyBody`, and one that doesn't parse with `400` and `InvalidQuery`.
+/// All of them are logged.
+async fn read_json_body<T: DeserializeOwned>(
+	body: Body, limit: usize, lenient: bool,
+) -> Result<T, Response<String>> {
+	let bytes = match read_limited_body(body, limit).await {
+		Ok(Some(bytes)) => bytes,
+		Ok(None) => {
+			println!("The request body is over {} bytes", limit);
+			let res = Response::builder()
+				.status(PAYLOAD_TOO_LARGE)
+				.body(ResponseBody::PayloadTooLarge.to_string())
+				.unwrap();
+			return Err(res);
+		},
+		Err(e) => {
+			println!("Failed to read the request body: {:?}", e);
+			let res = Response::builder()
+				.status(REQUEST_TIMEOUT)
+				.body(ResponseBody::IncompleteBody.to_string())
+				.unwrap();
+			return Err(res);
+		},
+	};
+	if bytes.iter().all(u8::is_ascii_whitespace) {
+		println!("The request body is empty");
+		let res = Response::builder()
+			.status(BAD_REQUEST)
+			.body(ResponseBody::EmptyBody.to_string())
+			.unwrap();
+		return Err(res);
+	}
+	let parsed = if lenient {
+		let text = std::str::from_utf8(&bytes).map_err(|e| e.to_string());
+		text.and_then(|text| json5::from_str(text).map_err(|e| e.to_string()))
+	} else {
+		serde_json::from_slice(&bytes).map_err(|e| e.to_string())
+	};
+	parsed.map_err(|e| {
+		println!("Failed to parse the request body: {}", e);
+		Response::builder()
+			.status(BAD_REQUEST)
+			.body(ResponseBody::InvalidQuery.to_string())
+			.unwrap()
+	})
+}
+
+/// Read the whole body, giving up as soon as it grows past `limit` bytes.
+/// Returns `None` if the body is too large.
+async fn read_limited_body(mut body: Body, limit: usize) -> Result<Option<Vec<u8>>, EigenError> {
+	let mut bytes = Vec::new();
+	while let Some(chunk) = body.data().await {
+		let chunk = chunk.map_err(|_| EigenError::ConnectionError)?;
+		if bytes.len() + chunk.len() > limit {
+			return Ok(None);
+		}
+		bytes.extend_from_slice(&chunk);
+	}
+	Ok(Some(bytes))
+}
+
+/// Check if the client advertises gzip in its `Accept-Encoding` header
+fn accepts_gzip(req: &Request<Body>) -> bool {
+	let header = req.headers().get(ACCEPT_ENCODING).and_then(|h| h.to_str().ok());
+	header.map_or(false, |encodings| {
+		encodings.split(',').any(|enc| {
+			let mut parts = enc.split(';').map(str::trim);
+			let is_gzip = parts.next().map_or(false, |name| name.eq_ignore_ascii_case("gzip"));
+			let disabled = parts.any(|param| param.replace(' ', "") == "q=0");
+			is_gzip && !disabled
+		})
+	})
+}
+
+/// Check if the client asks for JSON in its `Accept` header
+fn accepts_json(req: &Request<Body>) -> bool {
+	let header = req.headers().get(ACCEPT).and_then(|h| h.to_str().ok());
+	header.map_or(false, |types| {
+		types.split(',').any(|t| {
+			let media_type = t.split(';').next().unwrap_or_default().trim();
+			media_type.eq_ignore_ascii_case("application/json")
+		})
+	})
+}
+
+/// Serve the HTTP/1 requests arriving on the stream until the connection is
+/// closed, compressing the responses of `COMPRESSED_ROUTES` for clients
+/// accepting gzip. Errors are logged, since there is no one left to answer.
+async fn handle_connection<S>(stream: S, arc_manager: Arc<Mutex<Manager>>, keep_alive: bool)
+where
+	S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+	let mut https = Http::new();
+	https.http1_keep_alive(keep_alive);
+
+	let service_function = service_fn(move |req: Request<Body>| {
+		let arc_manager = Arc::clone(&arc_manager);
+		async move {
+			let compress = COMPRESSED_ROUTES.contains(&req.uri().path());
+			let gzip = compress && accepts_gzip(&req);
+			let res = handle_traced_request(req, arc_manager).await?;
+			Ok::<_, EigenError>(encode_response(res, gzip))
+		}
+	});
+	let res = https.serve_connection(stream, service_function).await;
+	if let Err(err) = res {
+		println!("Error serving connection: {:?}", err);
+	}
+}
+
+/// Convert the response into a hyper body, compressing it with gzip when
+/// requested. Falls back to the uncompressed body if compression fails.
+fn encode_response(res: Response<String>, gzip: bool) -> Response<Body> {
+	if !gzip {
+		return res.map(Body::from);
+	}
+
+	let (mut parts, body) = res.into_parts();
+	let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+	match encoder.write_all(body.as_bytes()).and_then(|_| encoder.finish()) {
+		Ok(bytes) => {
+			parts.headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+			parts.headers.insert(VARY, HeaderValue::from_static("accept-encoding"));
+			Response::from_parts(parts, Body::from(bytes))
+		},
+		Err(e) => {
+			println!("Failed to compress the response: {:?}", e);
+			Response::from_parts(parts, Body::from(body))
+		},
+	}
+}
+
+/// Take the request id from the `X-Request-ID` header, or generate a random
+/// one when it is missing, too long or not printable ASCII
+fn request_id(req: &Request<Body>) -> String {
+	let header = req.headers().get(REQUEST_ID_HEADER).and_then(|h| h.to_str().ok());
+	let valid = |id: &&str| {
+		!id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.bytes().all(|b| b.is_ascii_graphic())
+	};
+	match header.filter(valid) {
+		Some(id) => id.to_string(),
+		None => format!("{:016x}", thread_rng().gen::<u64>()),
+	}
+}
+
+/// Handle the request under its id, logging when it starts and ends, and
+/// echoing the id back in the `X-Request-ID` response header
+async fn handle_traced_request(
+	req: Request<Body>, arc_manager: Arc<Mutex<Manager>>,
+) -> Result<Response<String>, EigenError> {
+	let id = request_id(&req);
+	println!("[{}] {} {}", id, req.method(), req.uri().path());
+	let res = handle_with_deadline(req, arc_manager, *REQUEST_DEADLINE).await;
+	match res {
+		Ok(mut res) => {
+			println!("[{}] {}", id, res.status());
+			// Generated ids and the ones passing the check are valid values
+			if let Ok(value) = HeaderValue::from_str(&id) {
+				res.headers_mut().insert(REQUEST_ID_HEADER, value);
+			}
+			Ok(res)
+		},
+		Err(e) => {
+			println!("[{}] error: {:?}", id, e);
+			Err(e)
+		},
+	}
+}
+
+/// Handle the request within the deadline, answering with `503` and a
+/// `Retry-After` header when it runs out. The deadline is only checked while
+/// the handler is waiting, like on the request body or the convergence check,
+/// so a handler blocked on the manager lock, or computing under it, runs to
+/// the end and is only answered late.
+async fn handle_with_deadline(
+	req: Request<Body>, arc_manager: Arc<Mutex<Manager>>, deadline: Option<Duration>,
+) -> Result<Response<String>, EigenError> {
+	let deadline = match deadline {
+		Some(deadline) => deadline,
+		None => return handle_idempotent_request(req, arc_manager).await,
+	};
+	match time::timeout(deadline, handle_idempotent_request(req, arc_manager)).await {
+		Ok(res) => res,
+		Err(_) => {
+			let res = Response::builder()
+				.status(SERVICE_UNAVAILABLE)
+				.header(RETRY_AFTER, DEADLINE_RETRY_AFTER_SECS)
+				.body(ResponseBody::DeadlineExceeded.to_string())
+				.unwrap();
+			Ok(res)
+		},
+	}
+}
+
+/// Handle the request, replaying the stored response when a submission is
+/// retried with the same `Idempotency-Key`. Only responses below `500` are
+/// stored, so a failed submission can be retried. Duplicates arriving while the
+/// first one is still processed are not caught, and are both handled.
+async fn handle_idempotent_request(
+	req: Request<Body>, arc_manager: Arc<Mutex<Manager>>,
+) -> Result<Response<String>, EigenError> {
+	let path = req.uri().path();
+	let key = req.headers().get(IDEMPOTENCY_KEY_HEADER).and_then(|h| h.to_str().ok());
+	let key = match key {
+		Some(key) if req.method() == Method::POST && IDEMPOTENT_ROUTES.contains(&path) => {
+			format!("{} {}", path, key)
+		},
+		_ => return handle_request(req, arc_manager).await,
+	};
+
+	let stored = IDEMPOTENCY_CACHE.lock().ok().and_then(|cache| cache.get(&key));
+	if let Some(stored) = stored {
+		let res = Response::builder().status(stored.status).body(stored.body).unwrap();
+		return Ok(res);
+	}
+
+	let res = handle_request(req, arc_manager).await?;
+	let status = res.status().as_u16();
+	if status < INTERNAL_SERVER_ERROR {
+		if let Ok(mut cache) = IDEMPOTENCY_CACHE.lock() {
+			cache.insert(key, StoredResponse { status, body: res.body().clone() });
+		}
+	}
+	Ok(res)
+}
+
 async fn handle_request(
 	req: Request<Body>, arc_manager: Arc<Mutex<Manager>>,
 ) -> Result<Response<String>, EigenError> {
 	match (req.method(), req.uri().path()) {
 		(&Method::GET, "/score") => {
+			let query = parse_query(req.uri());
+			let epoch = query_epoch(&query, "epoch");
+			if epoch.is_err() {
+				let res = Response::builder()
+					.status(BAD_REQUEST)
+					.body(ResponseBody::InvalidQuery.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+
+			if matches!(epoch, Ok(Some(epoch)) if is_future_epoch(epoch)) {
+				let res = Response::builder()
+					.status(BAD_REQUEST)
+					.body(ResponseBody::FutureEpoch.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+
 			let manager = arc_manager.lock();
 			if manager.is_err() {
 				let res = Response::builder()
@@ -95,144 +908,2289 @@ async fn handle_request(
 					.unwrap();
 				return Ok(res);
 			}
-			let m = manager.unwrap();
-			let proof = m.get_last_proof();
+			let mut m = manager.unwrap();
+			let epoch = epoch.unwrap().map_or_else(|| m.get_last_epoch(), Ok);
+			if epoch.is_err() {
+				// Tell waiting for the attestations apart from a wrong epoch,
+				// and from a server that never makes a proof
+				let (submitted, required) = m.participation();
+				let e = match epoch.err().unwrap() {
+					EigenError::ProofNotFound { epoch: None } if submitted < required => {
+						EigenError::IncompleteParticipation { submitted, required }
+					},
+					_ if !m.is_proving_enabled() => EigenError::ProvingDisabled,
+					e => e,
+				};
+				return Ok(error_response(e));
+			}
+			let epoch = epoch.unwrap();
+			let proof = serve_proof(&mut m, epoch);
 			if proof.is_err() {
-				println!("{:?}", proof.err().unwrap());
+				return Ok(error_response(proof.err().unwrap()));
+			}
+			let proof = ProofRaw::from(proof.unwrap());
+			let res = Response::builder()
+				.header(EPOCH_HEADER, epoch.0)
+				.body(ResponseBody::Score(proof).to_string())
+				.unwrap();
+			return Ok(res);
+		},
+		(&Method::GET, "/score/history") => {
+			let query = parse_query(req.uri());
+			let pk = query.get("pk").and_then(|pk| public_key_from_str(pk));
+			let from = query_epoch(&query, "from");
+			let to = query_epoch(&query, "to");
+			if pk.is_none() || from.is_err() || to.is_err() {
 				let res = Response::builder()
 					.status(BAD_REQUEST)
 					.body(ResponseBody::InvalidQuery.to_string())
 					.unwrap();
 				return Ok(res);
 			}
-			let proof = ProofRaw::from(proof.unwrap());
-			let res = Response::new(ResponseBody::Score(proof).to_string());
+
+			let manager = arc_manager.lock();
+			if manager.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let m = manager.unwrap();
+			let history = m.get_score_history(&pk.unwrap(), from.unwrap(), to.unwrap());
+			if history.is_err() {
+				return Ok(error_response(history.err().unwrap()));
+			}
+			let history = history
+				.unwrap()
+				.into_iter()
+				.map(|(epoch, score)| ScoreEntry { epoch: epoch.0, score: field_to_string(&score) })
+				.collect();
+			let res = Response::new(ResponseBody::ScoreHistory(history).to_string());
 			return Ok(res);
 		},
-		_ => {
-			return Ok(Response::builder()
-				.status(NOT_FOUND)
-				.body(ResponseBody::InvalidRequest.to_string())
-				.unwrap())
-		},
-	}
-}
-
-#[tokio::main]
-async fn main() -> Result<(), EigenError> {
-	let config: ProtocolConfig = read_json_data("protocol-config").unwrap();
-
-	let addr: SocketAddr = config.endpoint.into();
-	let listener = TcpListener::bind(addr).await.map_err(|_| EigenError::ListenError)?;
-	println!("Listening on https://{}", addr);
-
-	let interval = Duration::from_secs(config.epoch_interval);
-	let mut inner_interval = time::interval(interval);
-	inner_interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
-
-	let mng_store = Arc::clone(&MANAGER_STORE);
-	let mut manager = mng_store.lock().unwrap();
-	manager.generate_initial_attestations();
-	drop(manager);
-
-	let client = setup_client(&config.ethereum_node_url);
-	let filter = Filter::new().from_block(0).address(ValueOrArray::Value(
-		config.as_contract_address.parse::<Address>().unwrap(),
-	));
-	let att_created_event = AttestationCreatedFilter::new(filter, &client);
-	let mut event_stream = att_created_event.stream().await.unwrap();
+		(&Method::GET, "/score/average") => {
+			let query = parse_query(req.uri());
+			let pk = query.get("pk").and_then(|pk| public_key_from_str(pk));
+			let epochs = query.get("epochs").and_then(|n| n.parse::<usize>().ok());
+			let (pk, epochs) = match (pk, epochs) {
+				(Some(pk), Some(epochs)) if epochs > 0 => (pk, epochs),
+				_ => {
+					let res = Response::builder()
+						.status(BAD_REQUEST)
+						.body(ResponseBody::InvalidQuery.to_string())
+						.unwrap();
+					return Ok(res);
+				},
+			};
 
-	loop {
-		select! {
-			listen_res = listener.accept() => {
-				let (stream, _) = listen_res.map_err(|_| EigenError::ConnectionError)?;
-				let mut https = Http::new();
-				https.http1_keep_alive(false);
-
-				let service_function = service_fn(async move |req| {
-					let mng_store = Arc::clone(&MANAGER_STORE);
-					handle_request(req, mng_store).await
-				});
-				let res = https.serve_connection(stream, service_function).await;
-				if let Err(err) = res {
-					println!("Error serving connection: {:?}", err);
-				}
+			let manager = arc_manager.lock();
+			if manager.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
 			}
-			_tick_res = inner_interval.tick() => {
-				let epoch = Epoch::current_epoch(config.epoch_interval);
-				let manager = mng_store.lock();
-
-				if manager.is_err() {
-					let e = manager.err();
-					println!("error: {:?}", e);
-				} else {
-					let mut manager = manager.unwrap();
-					manager.calculate_proofs(epoch).unwrap();
-				}
+			let m = manager.unwrap();
+			let average = m.average_normalized_score(&pk, epochs);
+			if average.is_err() {
+				return Ok(error_response(average.err().unwrap()));
 			}
-			event_res = event_stream.next() => {
-				 if let Some(Ok(att_created)) = event_res {
-					let AttestationCreatedFilter { val, .. } = att_created;
-
-					let att_data = AttestationData::from_bytes(val.to_vec());
-					let att = Attestation::from(att_data.clone());
+			let (score, epochs) = average.unwrap();
+			let average = AverageScore { pk: public_key_to_bs58(&pk), score, epochs };
+			let res = Response::new(ResponseBody::AverageScore(average).to_string());
+			return Ok(res);
+		},
+		(&Method::GET, "/ranking-diff") => {
+			let query = parse_query(req.uri());
+			let a = query_epoch(&query, "a");
+			let b = query_epoch(&query, "b");
+			let (a, b) = match (a, b) {
+				(Ok(Some(a)), Ok(Some(b))) => (a, b),
+				_ => {
+					let res = Response::builder()
+						.status(BAD_REQUEST)
+						.body(ResponseBody::InvalidQuery.to_string())
+						.unwrap();
+					return Ok(res);
+				},
+			};
 
-					let mng_store = Arc::clone(&MANAGER_STORE);
-					let mut manager = mng_store.lock().unwrap();
-					manager.add_attestation(att).unwrap();
-				}
+			let manager = arc_manager.lock();
+			if manager.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
 			}
-		};
-	}
-}
+			let m = manager.unwrap();
+			let changes = m.ranking_diff(a, b);
+			if changes.is_err() {
+				return Ok(error_response(changes.err().unwrap()));
+			}
+			let res = Response::new(ResponseBody::RankingDiff(changes.unwrap()).to_string());
+			return Ok(res);
+		},
+		(&Method::GET, "/score/transcript") => {
+			let manager = arc_manager.lock();
+			if manager.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let mut m = manager.unwrap();
+			// No proof is ever made to be served
+			if !m.is_proving_enabled() {
+				return Ok(error_response(EigenError::ProvingDisabled));
+			}
+			let proof =
+				m.get_last_epoch().and_then(|epoch| Ok((epoch, serve_proof(&mut m, epoch)?)));
+			if proof.is_err() {
+				return Ok(error_response(proof.err().unwrap()));
+			}
+			let (epoch, proof) = proof.unwrap();
 
-#[cfg(test)]
+			let transcript = bytes_to_hex(proof.transcript_bytes());
+			let res = Response::builder()
+				.header(EPOCH_HEADER, epoch.0)
+				.body(ResponseBody::Transcript(transcript).to_string())
+				.unwrap();
+			return Ok(res);
+		},
+		(&Method::GET, "/score/calldata") => {
+			let query = parse_query(req.uri());
+			let encoding = match query.get("chain") {
+				None => Some(CalldataEncoding::default()),
+				Some(name) => CalldataEncoding::from_name(name),
+			};
+			if encoding.is_none() {
+				let res = Response::builder()
+					.status(BAD_REQUEST)
+					.body(ResponseBody::InvalidQuery.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let encoding = encoding.unwrap();
+
+			let manager = arc_manager.lock();
+			if manager.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let mut m = manager.unwrap();
+			// No proof is ever made to be served
+			if !m.is_proving_enabled() {
+				return Ok(error_response(EigenError::ProvingDisabled));
+			}
+			let proof =
+				m.get_last_epoch().and_then(|epoch| Ok((epoch, serve_proof(&mut m, epoch)?)));
+			if proof.is_err() {
+				return Ok(error_response(proof.err().unwrap()));
+			}
+			let (epoch, proof) = proof.unwrap();
+
+			let calldata = bytes_to_hex(&encoding.encode(&proof));
+			let res = Response::builder()
+				.header(EPOCH_HEADER, epoch.0)
+				.body(ResponseBody::Calldata(calldata).to_string())
+				.unwrap();
+			return Ok(res);
+		},
+		(&Method::GET, "/score/normalized") => {
+			let query = parse_query(req.uri());
+			let log_scale = match query.get("scale").map(String::as_str) {
+				None | Some("linear") => false,
+				Some("log") => true,
+				Some(_) => {
+					let res = Response::builder()
+						.status(BAD_REQUEST)
+						.body(ResponseBody::InvalidQuery.to_string())
+						.unwrap();
+					return Ok(res);
+				},
+			};
+			let raw = query.get("raw").map_or(false, |v| v == "true");
+			if raw && log_scale {
+				let res = Response::builder()
+					.status(BAD_REQUEST)
+					.body(ResponseBody::InvalidQuery.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+
+			let manager = arc_manager.lock();
+			if manager.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let m = manager.unwrap();
+			let (_, pks) = keyset_from_raw(FIXED_SET);
+			if raw {
+				let scores =
+					m.get_last_epoch().and_then(|epoch| Ok((epoch, m.public_inputs(epoch)?)));
+				if scores.is_err() {
+					return Ok(error_response(scores.err().unwrap()));
+				}
+				let (epoch, scores) = scores.unwrap();
+
+				let scores = pks
+					.iter()
+					.zip(scores)
+					.map(|(pk, score)| RawScore {
+						pk: public_key_to_bs58(pk),
+						score: field_to_string(&score),
+					})
+					.collect();
+				let res = Response::builder()
+					.header(EPOCH_HEADER, epoch.0)
+					.body(ResponseBody::RawScores(scores).to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let scores = m.get_last_epoch().and_then(|epoch| {
+				let scores = if log_scale {
+					m.normalized_scores_log(epoch)?
+				} else {
+					m.checked_normalized_scores(epoch)?
+				};
+				Ok((epoch, scores))
+			});
+			if scores.is_err() {
+				return Ok(error_response(scores.err().unwrap()));
+			}
+			let (epoch, scores) = scores.unwrap();
+
+			let scores = pks
+				.iter()
+				.zip(scores)
+				.map(|(pk, score)| NormalizedScore { pk: public_key_to_bs58(pk), score })
+				.collect();
+			let res = Response::builder()
+				.header(EPOCH_HEADER, epoch.0)
+				.body(ResponseBody::NormalizedScores(scores).to_string())
+				.unwrap();
+			return Ok(res);
+		},
+		(&Method::GET, "/scores") => {
+			let query = parse_query(req.uri());
+			let pks: Option<Vec<PublicKey>> =
+				query.get("pks").and_then(|pks| pks.split(',').map(public_key_from_str).collect());
+			let epoch = query_epoch(&query, "epoch");
+			if pks.is_none() || epoch.is_err() {
+				let res = Response::builder()
+					.status(BAD_REQUEST)
+					.body(ResponseBody::InvalidQuery.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+
+			if matches!(epoch, Ok(Some(epoch)) if is_future_epoch(epoch)) {
+				let res = Response::builder()
+					.status(BAD_REQUEST)
+					.body(ResponseBody::FutureEpoch.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let pks = pks.unwrap();
+
+			let manager = arc_manager.lock();
+			if manager.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let m = manager.unwrap();
+			let scores = epoch
+				.unwrap()
+				.map_or_else(|| m.get_last_epoch(), Ok)
+				.and_then(|epoch| Ok((epoch, m.scores_for(&pks, epoch)?)));
+			if scores.is_err() {
+				return Ok(error_response(scores.err().unwrap()));
+			}
+			let (epoch, scores) = scores.unwrap();
+
+			let found = scores.iter().map(Option::is_some).collect();
+			let scores = scores.iter().map(|score| score.as_ref().map(field_to_string)).collect();
+			let ordered_scores = OrderedScores { epoch: epoch.0, scores, found };
+			let res = Response::builder()
+				.header(EPOCH_HEADER, epoch.0)
+				.body(ResponseBody::OrderedScores(ordered_scores).to_string())
+				.unwrap();
+			return Ok(res);
+		},
+		(&Method::GET, "/score/signed") => {
+			let query = parse_query(req.uri());
+			let pk = query.get("pk").and_then(|pk| public_key_from_str(pk));
+			let epoch = query_epoch(&query, "epoch");
+			if pk.is_none() || epoch.is_err() {
+				let res = Response::builder()
+					.status(BAD_REQUEST)
+					.body(ResponseBody::InvalidQuery.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+
+			if matches!(epoch, Ok(Some(epoch)) if is_future_epoch(epoch)) {
+				let res = Response::builder()
+					.status(BAD_REQUEST)
+					.body(ResponseBody::FutureEpoch.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let pk = pk.unwrap();
+
+			let manager = arc_manager.lock();
+			if manager.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let m = manager.unwrap();
+			let score = epoch
+				.unwrap()
+				.map_or_else(|| m.get_last_epoch(), Ok)
+				.and_then(|epoch| Ok((epoch, m.get_score(&pk, epoch)?)));
+			if score.is_err() {
+				return Ok(error_response(score.err().unwrap()));
+			}
+			let (epoch, score) = score.unwrap();
+
+			let message_hash = score_message_hash(&pk, epoch, score);
+			let sig = sign(server_key(), &server_key().public(), message_hash);
+			let signed_score = SignedScore {
+				pk: public_key_to_bs58(&pk),
+				epoch: epoch.0,
+				score: field_to_string(&score),
+				sig_r_x: scalar_to_hex(&sig.big_r.x),
+				sig_r_y: scalar_to_hex(&sig.big_r.y),
+				sig_s: scalar_to_hex(&sig.s),
+			};
+			let res = Response::builder()
+				.header(EPOCH_HEADER, epoch.0)
+				.body(ResponseBody::SignedScore(signed_score).to_string())
+				.unwrap();
+			return Ok(res);
+		},
+		(&Method::GET, "/status") => {
+			// Doesn't lock the manager, which is held for the whole convergence
+			let next_epoch_in_s =
+				Epoch::secs_until_next_epoch(EPOCH_INTERVAL.load(Ordering::SeqCst));
+			if next_epoch_in_s.is_err() {
+				return Ok(error_response(next_epoch_in_s.err().unwrap()));
+			}
+			let last = LAST_CONVERGENCE.lock();
+			if last.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let last = *last.unwrap();
+			let progress = CONVERGENCE_PROGRESS.lock();
+			if progress.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let progress = *progress.unwrap();
+			let manager = arc_manager.try_lock().ok();
+			let participation = manager.as_ref().map(|m| {
+				let (submitted, required) = m.participation();
+				Participation { submitted, required }
+			});
+			let estimated_proving_ms =
+				manager.as_ref().map(|m| m.estimate_proving_time().as_millis() as u64);
+			let status = ConvergenceStatus {
+				converging: CONVERGENCE_IN_PROGRESS.load(Ordering::SeqCst),
+				progress,
+				last_epoch: last.map(|(epoch, _)| epoch.0),
+				last_duration_ms: last.map(|(_, duration_ms)| duration_ms),
+				next_epoch_in_s: next_epoch_in_s.unwrap(),
+				participation,
+				estimated_proving_ms,
+			};
+			let res = Response::new(ResponseBody::Status(status).to_string());
+			return Ok(res);
+		},
+		(&Method::GET, "/metrics") => {
+			let manager = arc_manager.lock();
+			if manager.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let metrics = format_metrics(&manager.unwrap());
+			let res = Response::builder()
+				.header(CONTENT_TYPE, "text/plain; version=0.0.4")
+				.body(ResponseBody::Metrics(metrics).to_string())
+				.unwrap();
+			return Ok(res);
+		},
+		(&Method::GET, "/server-pubkey") => {
+			let pk = public_key_to_bs58(&server_key().public());
+			let res = Response::new(ResponseBody::ServerPubkey(pk).to_string());
+			return Ok(res);
+		},
+		(&Method::GET, "/epochs") => {
+			let manager = arc_manager.lock();
+			if manager.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let m = manager.unwrap();
+			let epochs = m.available_epochs().into_iter().map(|epoch| epoch.0).collect();
+			let res = Response::new(ResponseBody::Epochs(epochs).to_string());
+			return Ok(res);
+		},
+		(&Method::GET, "/validate") => {
+			let manager = arc_manager.lock();
+			if manager.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let m = manager.unwrap();
+			let report = ValidationReport { untrusted: m.untrusted_participants() };
+			let res = Response::new(ResponseBody::Validation(report).to_string());
+			return Ok(res);
+		},
+		(&Method::GET, "/attestation-root") => {
+			let manager = arc_manager.lock();
+			if manager.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let m = manager.unwrap();
+			let root = scalar_to_hex(&m.attestation_root());
+			let res = Response::new(ResponseBody::AttestationRoot(root).to_string());
+			return Ok(res);
+		},
+		(&Method::GET, "/is-member") => {
+			let query = parse_query(req.uri());
+			let pk = query.get("pk").and_then(|pk| public_key_from_str(pk));
+			if pk.is_none() {
+				let res = Response::builder()
+					.status(BAD_REQUEST)
+					.body(ResponseBody::InvalidQuery.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let is_member = Manager::is_member(&pk.unwrap());
+			let res = Response::new(ResponseBody::IsMember(is_member).to_string());
+			return Ok(res);
+		},
+		(&Method::GET, "/pk-hash") => {
+			let query = parse_query(req.uri());
+			let pk = query.get("pk").and_then(|pk| public_key_from_str(pk));
+			if pk.is_none() {
+				let res = Response::builder()
+					.status(BAD_REQUEST)
+					.body(ResponseBody::InvalidQuery.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let pk_hash = scalar_to_hex(&hash_public_key(&pk.unwrap()));
+			let res = Response::new(ResponseBody::PkHash(pk_hash).to_string());
+			return Ok(res);
+		},
+		(&Method::GET, "/attestation/message") => {
+			let query = parse_query(req.uri());
+			let pks: Option<Vec<PublicKey>> = query
+				.get("neighbours")
+				.and_then(|pks| pks.split(',').map(public_key_from_str).collect());
+			let scores: Option<Vec<u128>> = query
+				.get("scores")
+				.and_then(|scores| scores.split(',').map(|x| x.parse().ok()).collect());
+			let (pks, scores) = match (pks, scores) {
+				(Some(pks), Some(scores))
+					if pks.len() == NUM_NEIGHBOURS && scores.len() == NUM_NEIGHBOURS =>
+				{
+					(pks, scores)
+				},
+				_ => {
+					let res = Response::builder()
+						.status(BAD_REQUEST)
+						.body(ResponseBody::InvalidQuery.to_string())
+						.unwrap();
+					return Ok(res);
+				},
+			};
+
+			let scores: Vec<Scalar> = scores.into_iter().map(Scalar::from_u128).collect();
+			let (pks_hash, messages) =
+				calculate_message_hash::<NUM_NEIGHBOURS, 1>(pks.clone(), vec![scores.clone()]);
+			let neighbours = pks
+				.iter()
+				.map(|pk| MessageNeighbour {
+					pk: public_key_to_bs58(pk),
+					x: scalar_to_hex(&pk.0.x),
+					y: scalar_to_hex(&pk.0.y),
+				})
+				.collect();
+			let message = AttestationMessage {
+				neighbours,
+				scores: scores.iter().map(field_to_string).collect(),
+				pks_hash: scalar_to_hex(&pks_hash),
+				message_hash: scalar_to_hex(&messages[0]),
+			};
+			let res = Response::new(ResponseBody::AttestationMessage(message).to_string());
+			return Ok(res);
+		},
+		(&Method::POST, "/verify-attestation") => {
+			let lenient = is_lenient(&req);
+			let att =
+				read_json_body::<AttestationData>(req.into_body(), MAX_RECORD_BYTES, lenient).await;
+			if att.is_err() {
+				return Ok(att.err().unwrap());
+			}
+			let att = Attestation::from(att.unwrap());
+			if att.scheme != SignatureScheme::EdDsa {
+				return Ok(error_response(EigenError::UnsupportedSignatureScheme));
+			}
+
+			let is_valid = Manager::verify_attestation_signature(&att);
+			let res = Response::new(ResponseBody::SignatureValid(is_valid).to_string());
+			return Ok(res);
+		},
+		(&Method::POST, "/attestations") => {
+			let content_length = req
+				.headers()
+				.get(CONTENT_LENGTH)
+				.and_then(|x| x.to_str().ok())
+				.and_then(|x| x.parse::<u64>().ok());
+			if content_length.map_or(false, |len| len > MAX_BATCH_BYTES) {
+				let res = Response::builder()
+					.status(PAYLOAD_TOO_LARGE)
+					.body(ResponseBody::PayloadTooLarge.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+
+			let results = add_attestation_batch(req.into_body(), &arc_manager).await;
+			if results.is_err() {
+				return Ok(error_response(results.err().unwrap()));
+			}
+			let res = Response::new(ResponseBody::BatchResults(results.unwrap()).to_string());
+			return Ok(res);
+		},
+		(&Method::POST, "/verify") => {
+			let content_length = req
+				.headers()
+				.get(CONTENT_LENGTH)
+				.and_then(|x| x.to_str().ok())
+				.and_then(|x| x.parse::<usize>().ok());
+			if content_length.map_or(false, |len| len > MAX_PROOF_BYTES) {
+				let res = Response::builder()
+					.status(PAYLOAD_TOO_LARGE)
+					.body(ResponseBody::PayloadTooLarge.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+
+			// The body is capped, so parsing it afterwards takes bounded time
+			let body = read_limited_body(req.into_body(), MAX_PROOF_BYTES);
+			let bytes = match time::timeout(PROOF_READ_TIMEOUT, body).await {
+				Ok(Ok(Some(bytes))) => bytes,
+				Ok(Ok(None)) => {
+					let res = Response::builder()
+						.status(PAYLOAD_TOO_LARGE)
+						.body(ResponseBody::PayloadTooLarge.to_string())
+						.unwrap();
+					return Ok(res);
+				},
+				Ok(Err(e)) => {
+					println!("Failed to read the request body: {:?}", e);
+					let res = Response::builder()
+						.status(REQUEST_TIMEOUT)
+						.body(ResponseBody::IncompleteBody.to_string())
+						.unwrap();
+					return Ok(res);
+				},
+				Err(_) => {
+					let res = Response::builder()
+						.status(REQUEST_TIMEOUT)
+						.body(ResponseBody::RequestTimeout.to_string())
+						.unwrap();
+					return Ok(res);
+				},
+			};
+			let proof = serde_json::from_slice::<ProofRaw>(&bytes)
+				.ok()
+				.and_then(|raw| Proof::try_from(raw).ok());
+			if proof.is_none() {
+				let res = Response::builder()
+					.status(BAD_REQUEST)
+					.body(ResponseBody::InvalidQuery.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let proof = proof.unwrap();
+
+			// Verify on a blocking task, like the proving does
+			let is_valid = spawn_blocking(move || {
+				arc_manager.lock().ok().map(|manager| manager.verify_proof(&proof))
+			})
+			.await;
+			let res = match is_valid {
+				Ok(Some(is_valid)) => Response::new(ResponseBody::ProofValid(is_valid).to_string()),
+				Ok(None) => Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap(),
+				Err(e) => {
+					println!("{:?}", e);
+					error_response(EigenError::VerificationError)
+				},
+			};
+			return Ok(res);
+		},
+		(&Method::POST, "/attestation/replace") => {
+			let lenient = is_lenient(&req);
+			let att =
+				read_json_body::<AttestationData>(req.into_body(), MAX_RECORD_BYTES, lenient).await;
+			if att.is_err() {
+				return Ok(att.err().unwrap());
+			}
+			let att = Attestation::from(att.unwrap());
+
+			// Same as new attestations, so the replacement is never half applied
+			// to the running convergence
+			if REJECT_DURING_CONVERGENCE.load(Ordering::SeqCst)
+				&& CONVERGENCE_IN_PROGRESS.load(Ordering::SeqCst)
+			{
+				return Ok(error_response(EigenError::ConvergenceInProgress));
+			}
+
+			let manager = arc_manager.lock();
+			if manager.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let mut m = manager.unwrap();
+			let replaced = m.replace_attestation(att);
+			if replaced.is_err() {
+				return Ok(error_response(replaced.err().unwrap()));
+			}
+			let res = Response::new(ResponseBody::Replaced(replaced.unwrap()).to_string());
+			return Ok(res);
+		},
+		(&Method::POST, "/prove") => {
+			// Proving takes the manager for the whole proof, so only the operator
+			// can ask for one
+			if !is_admin(&req) {
+				let res = Response::builder()
+					.status(FORBIDDEN)
+					.body(ResponseBody::Unauthorized.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+
+			let lenient = is_lenient(&req);
+			let body = req.into_body();
+			let atts = read_json_body::<Vec<AttestationData>>(body, MAX_PROVE_BYTES, lenient).await;
+			if atts.is_err() {
+				return Ok(atts.err().unwrap());
+			}
+			let atts: Vec<Attestation> = atts.unwrap().into_iter().map(Attestation::from).collect();
+
+			// Prove on a blocking task, like the epoch convergence does
+			let proof = spawn_blocking(move || {
+				arc_manager.lock().ok().map(|manager| manager.prove_attestations(atts))
+			})
+			.await;
+			let res = match proof {
+				Ok(Some(Ok(proof))) => {
+					Response::new(ResponseBody::Proof(ProofRaw::from(proof)).to_string())
+				},
+				Ok(Some(Err(e))) => error_response(e),
+				Ok(None) => Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap(),
+				Err(e) => {
+					println!("{:?}", e);
+					Response::builder()
+						.status(INTERNAL_SERVER_ERROR)
+						.body(ResponseBody::ProvingError.to_string())
+						.unwrap()
+				},
+			};
+			return Ok(res);
+		},
+		(&Method::GET, "/coverage") => {
+			if !is_admin(&req) {
+				let res = Response::builder()
+					.status(FORBIDDEN)
+					.body(ResponseBody::Unauthorized.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+
+			let manager = arc_manager.lock();
+			if manager.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let m = manager.unwrap();
+			let res = Response::new(ResponseBody::Coverage(m.coverage_matrix()).to_string());
+			return Ok(res);
+		},
+		(&Method::POST, "/score/distrust") => {
+			// Distrust is not signed by the participants, so only the operator
+			// can preview it
+			if !is_admin(&req) {
+				let res = Response::builder()
+					.status(FORBIDDEN)
+					.body(ResponseBody::Unauthorized.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+
+			let lenient = is_lenient(&req);
+			let body = req.into_body();
+			let entries =
+				read_json_body::<Vec<DistrustEntry>>(body, MAX_RECORD_BYTES, lenient).await;
+			if entries.is_err() {
+				return Ok(entries.err().unwrap());
+			}
+			let distrust: Option<Vec<_>> = entries
+				.unwrap()
+				.into_iter()
+				.map(|entry| public_key_from_str(&entry.pk).map(|pk| (pk, entry.distrust)))
+				.collect();
+			if distrust.is_none() {
+				let res = Response::builder()
+					.status(BAD_REQUEST)
+					.body(ResponseBody::InvalidQuery.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let distrust = distrust.unwrap();
+
+			let manager = arc_manager.lock();
+			if manager.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let m = manager.unwrap();
+			let scores = m.calculate_scores_with_distrust(&distrust, NUM_ITER);
+			if scores.is_err() {
+				return Ok(error_response(scores.err().unwrap()));
+			}
+
+			let (_, pks) = keyset_from_raw(FIXED_SET);
+			let scores = pks
+				.iter()
+				.zip(scores.unwrap())
+				.map(|(pk, score)| NormalizedScore { pk: public_key_to_bs58(pk), score })
+				.collect();
+			let res = Response::new(ResponseBody::NormalizedScores(scores).to_string());
+			return Ok(res);
+		},
+		(&Method::POST, "/reset") => {
+			if !is_admin(&req) {
+				let res = Response::builder()
+					.status(FORBIDDEN)
+					.body(ResponseBody::Unauthorized.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+
+			let manager = arc_manager.lock();
+			if manager.is_err() {
+				let res = Response::builder()
+					.status(INTERNAL_SERVER_ERROR)
+					.body(ResponseBody::LockError.to_string())
+					.unwrap();
+				return Ok(res);
+			}
+			let mut m = manager.unwrap();
+			let (attestations, proofs) = m.clear();
+			let counts = ResetCounts { attestations, proofs };
+			let res = Response::new(ResponseBody::Reset(counts).to_string());
+			return Ok(res);
+		},
+		_ => {
+			let path = req.uri().path();
+			let allowed: Vec<&str> = ROUTES
+				.iter()
+				.filter(|(route, _)| *route == path)
+				.map(|(_, method)| *method)
+				.collect();
+			if !allowed.is_empty() {
+				return Ok(Response::builder()
+					.status(METHOD_NOT_ALLOWED)
+					.header(ALLOW, allowed.join(", "))
+					.body(ResponseBody::MethodNotAllowed.to_string())
+					.unwrap());
+			}
+			// Clients asking for JSON get the available routes, to help finding
+			// the right one
+			if accepts_json(&req) {
+				let routes = ROUTES
+					.iter()
+					.map(|(path, method)| RouteInfo { path: *path, method: *method })
+					.collect();
+				return Ok(Response::builder()
+					.status(NOT_FOUND)
+					.header(CONTENT_TYPE, "application/json")
+					.body(ResponseBody::Routes(routes).to_string())
+					.unwrap());
+			}
+			return Ok(Response::builder()
+				.status(NOT_FOUND)
+				.body(ResponseBody::InvalidRequest.to_string())
+				.unwrap());
+		},
+	}
+}
+
+/// Calculate the proofs for the given epoch
+fn handle_epoch_convergence(arc_manager: Arc<Mutex<Manager>>, epoch: Epoch) {
+	let manager = arc_manager.lock();
+	if manager.is_err() {
+		let e = manager.err();
+		println!("error: {:?}", e);
+		return;
+	}
+	let mut manager = manager.unwrap();
+	// Scores are calculated natively on request, there is nothing to prove
+	if !manager.is_proving_enabled() {
+		return;
+	}
+	let untrusted = manager.untrusted_participants();
+	if !untrusted.is_empty() {
+		println!("warning: no attestation gives a score to {:?}", untrusted);
+	}
+	let estimate = manager.estimate_proving_time();
+	let interval = Duration::from_secs(EPOCH_INTERVAL.load(Ordering::SeqCst));
+	if estimate > interval {
+		println!(
+			"warning: proving is estimated to take {:?}, longer than the epoch interval of {:?}",
+			estimate, interval
+		);
+	}
+	let start = Instant::now();
+	let res = manager.calculate_proofs_with_progress(epoch, record_progress);
+	record_progress_reset();
+	if let Err(e) = res {
+		println!("error: {:?}", e);
+		return;
+	}
+	record_convergence(epoch, start.elapsed());
+
+	if let Some(url) = WEBHOOK_URL.get() {
+		match manager.get_proof(epoch) {
+			Ok(proof) => {
+				let (body, signature) = convergence_notification(epoch, &proof.pub_ins);
+				spawn(notify_webhook(url.clone(), body, signature));
+			},
+			Err(e) => println!("error: {:?}", e),
+		}
+	}
+}
+
+/// Build the notification of a finished convergence, with the scores of the
+/// fixed set, and its signature header. The body is signed with the server
+/// key over `notification_message_hash`, and the signature is sent as the
+/// hex encoded `r_x,r_y,s`.
+fn convergence_notification(epoch: Epoch, scores: &[Scalar]) -> (String, String) {
+	let (_, pks) = keyset_from_raw(FIXED_SET);
+	let scores = pks
+		.iter()
+		.zip(scores)
+		.map(|(pk, score)| NotifiedScore {
+			pk: public_key_to_bs58(pk),
+			score: field_to_string(score),
+		})
+		.collect();
+	let notification = ConvergenceNotification { epoch: epoch.0, scores };
+	let body = to_string(&notification).unwrap();
+
+	let message_hash = notification_message_hash(body.as_bytes());
+	let sig = sign(server_key(), &server_key().public(), message_hash);
+	let signature = [sig.big_r.x, sig.big_r.y, sig.s].map(|x| scalar_to_hex(&x)).join(",");
+	(body, signature)
+}
+
+/// Post the notification to the webhook, retrying failed deliveries with an
+/// exponential backoff. Any `2xx` response counts as delivered.
+async fn notify_webhook(url: Uri, body: String, signature: String) {
+	let client = hyper::Client::new();
+	for retry in 0..=WEBHOOK_RETRIES {
+		if retry > 0 {
+			time::sleep(WEBHOOK_RETRY_DELAY * 2u32.pow(retry - 1)).await;
+		}
+		let req = Request::post(url.clone())
+			.header(CONTENT_TYPE, "application/json")
+			.header(WEBHOOK_SIGNATURE_HEADER, signature.as_str())
+			.body(Body::from(body.clone()))
+			.unwrap();
+		match client.request(req).await {
+			Ok(res) if res.status().is_success() => return,
+			Ok(res) => println!("warning: webhook answered with {}", res.status()),
+			Err(e) => println!("warning: failed to reach the webhook: {:?}", e),
+		}
+	}
+	println!(
+		"error: webhook not notified after {} retries",
+		WEBHOOK_RETRIES
+	);
+}
+
+/// Keep the last milestone of the running convergence for the status
+fn record_progress(progress: ConvergenceProgress) {
+	match CONVERGENCE_PROGRESS.lock() {
+		Ok(mut last) => *last = Some(progress),
+		Err(e) => println!("error: {:?}", e),
+	}
+}
+
+/// Clear the milestone once the convergence is over
+fn record_progress_reset() {
+	match CONVERGENCE_PROGRESS.lock() {
+		Ok(mut last) => *last = None,
+		Err(e) => println!("error: {:?}", e),
+	}
+}
+
+/// Keep the epoch and duration of a finished convergence for the status
+fn record_convergence(epoch: Epoch, duration: Duration) {
+	match LAST_CONVERGENCE.lock() {
+		Ok(mut last) => *last = Some((epoch, duration.as_millis() as u64)),
+		Err(e) => println!("error: {:?}", e),
+	}
+}
+
+/// Number of threads in the rayon pool used for proving. Taken from the
+/// environment, then from the config, and otherwise leaves one core for the
+/// async runtime.
+fn proving_threads(config_threads: Option<usize>) -> usize {
+	let env_threads = std::env::var(PROVING_THREADS_VAR).ok().and_then(|x| x.parse().ok());
+	let default_threads =
+		|| available_parallelism().map_or(1, |n| n.get().saturating_sub(1).max(1));
+	env_threads.or(config_threads).filter(|n| *n > 0).unwrap_or_else(default_threads)
+}
+
+/// Add an attestation to the manager. When `reject_during_convergence` is
+/// set, attestations arriving while a convergence is running are rejected
+/// instead of waiting for the lock, so an accepted attestation is always
+/// included in the next convergence and never in the running one.
+fn handle_attestation(
+	arc_manager: Arc<Mutex<Manager>>, att: Attestation, reject_during_convergence: bool,
+) -> Result<(), EigenError> {
+	if reject_during_convergence && CONVERGENCE_IN_PROGRESS.load(Ordering::SeqCst) {
+		return Err(EigenError::ConvergenceInProgress);
+	}
+	let mut manager = arc_manager.lock().map_err(|_| EigenError::Unknown)?;
+	manager.add_attestation(att)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), EigenError> {
+	let config: ProtocolConfig = read_json_data("protocol-config").unwrap();
+	Manager::verify_fixed_set_consistency()?;
+	let sk = read_server_key().map_err(|e| {
+		println!("{} is not two comma separated bs58 strings", SERVER_KEY_VAR);
+		e
+	})?;
+	SERVER_KEY.set(sk).unwrap();
+
+	// Has to be set before the manager is created, since the key generation
+	// already uses the global pool
+	let num_threads = proving_threads(config.proving_threads);
+	if let Err(e) = ThreadPoolBuilder::new().num_threads(num_threads).build_global() {
+		println!("warning: failed to set the proving threads: {:?}", e);
+	}
+	println!(
+		"Server public key: {}",
+		public_key_to_bs58(&server_key().public())
+	);
+	if config.epoch_interval == 0 {
+		return Err(EigenError::InvalidEpochInterval);
+	}
+	EPOCH_INTERVAL.store(config.epoch_interval, Ordering::SeqCst);
+	let reject = config.reject_attestations_during_convergence;
+	REJECT_DURING_CONVERGENCE.store(reject, Ordering::SeqCst);
+	VERIFY_BEFORE_SERVE.store(config.verify_proofs_before_serving, Ordering::SeqCst);
+	// Has to be set before the manager is created
+	NATIVE_ONLY.store(config.native_only, Ordering::SeqCst);
+
+	let addr: SocketAddr = config.endpoint.into();
+	let listener = TcpListener::bind(addr).await.map_err(|_| EigenError::ListenError)?;
+	println!("Listening on https://{}", addr);
+
+	let interval = Duration::from_secs(config.epoch_interval);
+	let mut inner_interval = time::interval(interval);
+	inner_interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+
+	let mng_store = Arc::clone(&MANAGER_STORE);
+	let mut manager = mng_store.lock().unwrap();
+	manager.set_max_cached_epochs(config.max_cached_epochs);
+	manager.set_score_floor(Score::new(config.score_floor)?);
+	manager.set_strict_score_sum(config.strict_score_sum);
+	manager.generate_initial_attestations();
+	if let Some(url) = &config.webhook_url {
+		let url = url.parse::<Uri>().map_err(|_| EigenError::ConnectionError)?;
+		WEBHOOK_URL.set(url).unwrap();
+	}
+	drop(manager);
+
+	let client = setup_client(&config.ethereum_node_url);
+	let filter = Filter::new().from_block(0).address(ValueOrArray::Value(
+		config.as_contract_address.parse::<Address>().unwrap(),
+	));
+	let att_created_event = AttestationCreatedFilter::new(filter, &client);
+	let mut event_stream = att_created_event.stream().await.unwrap();
+
+	loop {
+		select! {
+			listen_res = listener.accept() => {
+				let (stream, _) = listen_res.map_err(|_| EigenError::ConnectionError)?;
+
+				// Serve each connection on its own task, so a kept-alive connection
+				// doesn't block the epoch and event branches of this loop.
+				let mng_store = Arc::clone(&MANAGER_STORE);
+				spawn(handle_connection(stream, mng_store, config.keep_alive));
+			}
+			_tick_res = inner_interval.tick() => {
+				let epoch = match Epoch::current_epoch(config.epoch_interval) {
+					Ok(epoch) => epoch,
+					Err(e) => {
+						println!("error: {:?}", e);
+						continue;
+					},
+				};
+				if CONVERGENCE_IN_PROGRESS.swap(true, Ordering::SeqCst) {
+					println!("warning: convergence still in progress, skipping {}", epoch);
+					continue;
+				}
+
+				// Prove on a blocking task, so the loop keeps serving connections
+				// and events while the convergence runs.
+				let mng_store = Arc::clone(&MANAGER_STORE);
+				spawn_blocking(move || {
+					handle_epoch_convergence(mng_store, epoch);
+					CONVERGENCE_IN_PROGRESS.store(false, Ordering::SeqCst);
+				});
+			}
+			event_res = event_stream.next() => {
+				 if let Some(Ok(att_created)) = event_res {
+					let AttestationCreatedFilter { val, .. } = att_created;
+
+					let att_data = AttestationData::from_bytes(val.to_vec());
+					let att = Attestation::from(att_data.clone());
+
+					let mng_store = Arc::clone(&MANAGER_STORE);
+					let reject = config.reject_attestations_during_convergence;
+					if let Err(e) = handle_attestation(mng_store, att, reject) {
+						println!("Failed to add attestation: {:?}", e);
+					}
+				}
+			}
+		};
+	}
+}
+
+#[cfg(test)]
 mod test {
 	use super::*;
-	use eigen_trust_circuit::utils::keygen;
-	use hyper::Uri;
+	use eigen_trust_circuit::{
+		eddsa::native::{verify, Signature},
+		utils::keygen,
+	};
+	use eigen_trust_server::{manager::build_fixed_attestations, utils::generate_keypair};
+	use flate2::read::GzDecoder;
+	use hyper::{service::make_service_fn, Server};
 	use rand::thread_rng;
+	use std::{convert::Infallible, io::Read, sync::atomic::AtomicUsize};
+	use tokio::io::{duplex, AsyncReadExt, AsyncWriteExt};
+
+	/// Manager with a proving key, for the tests that make or serve proofs
+	fn proving_manager() -> Manager {
+		let params = read_params(14);
+		let random_circuit =
+			EigenTrust::<NUM_NEIGHBOURS, NUM_ITER, INITIAL_SCORE, SCALE>::random(&mut thread_rng());
+		let proving_key = keygen(&params, random_circuit).unwrap();
+		Manager::new(params, proving_key)
+	}
+
+	#[tokio::test]
+	async fn should_fail_if_route_is_not_found() {
+		let manager = Manager::new_native_only();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/non_existing_route"))
+			.body(Body::default())
+			.unwrap();
+
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(*res.body(), ResponseBody::InvalidRequest.to_string());
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/non_existing_route"))
+			.header(ACCEPT, "text/html, application/json;q=0.9")
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(res.status(), NOT_FOUND);
+		assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "application/json");
+		let routes: Vec<serde_json::Value> = serde_json::from_str(res.body()).unwrap();
+		assert_eq!(routes.len(), ROUTES.len());
+		assert_eq!(routes[0]["path"], "/score");
+		assert_eq!(routes[0]["method"], "GET");
+	}
+
+	#[tokio::test]
+	async fn should_fail_if_method_is_not_allowed() {
+		let manager = Manager::new_native_only();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let req = Request::post(Uri::from_static("http://localhost:3000/score"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(res.status(), METHOD_NOT_ALLOWED);
+		assert_eq!(res.headers().get(ALLOW).unwrap(), "GET");
+		assert_eq!(*res.body(), ResponseBody::MethodNotAllowed.to_string());
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/prove"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(res.status(), METHOD_NOT_ALLOWED);
+		assert_eq!(res.headers().get(ALLOW).unwrap(), "POST");
+	}
+
+	#[tokio::test]
+	async fn should_query_score() {
+		let mut manager = proving_manager();
+		manager.generate_initial_attestations();
+		let epoch = Epoch(0);
+		manager.calculate_proofs(epoch).unwrap();
+		let real_proof = manager.get_proof(epoch).unwrap();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/score"))
+			.body(Body::default())
+			.unwrap();
+
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		let proof_raw = ProofRaw::from(real_proof);
+		assert_eq!(*res.body(), to_string(&proof_raw).unwrap());
+		assert_eq!(res.headers().get(EPOCH_HEADER).unwrap(), "0");
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/score?epoch=0"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(*res.body(), to_string(&proof_raw).unwrap());
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/score?epoch=1"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(res.status(), NOT_FOUND);
+	}
+
+	#[tokio::test]
+	async fn should_report_incomplete_participation() {
+		let mut manager = proving_manager();
+		for att in build_fixed_attestations().into_iter().take(3) {
+			manager.add_attestation(att).unwrap();
+		}
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/score"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(res.status(), NOT_FOUND);
+		let e = EigenError::IncompleteParticipation { submitted: 3, required: NUM_NEIGHBOURS };
+		assert_eq!(*res.body(), ResponseBody::Error(e).to_string());
+
+		// Every participant attested, the proof is just not there yet
+		arc_manager.lock().unwrap().generate_initial_attestations();
+		let req = Request::get(Uri::from_static("http://localhost:3000/score"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		let e = EigenError::ProofNotFound { epoch: None };
+		assert_eq!(*res.body(), ResponseBody::Error(e).to_string());
+	}
+
+	#[tokio::test]
+	async fn should_reject_future_epoch() {
+		let manager = proving_manager();
+		let arc_manager = Arc::new(Mutex::new(manager));
+		EPOCH_INTERVAL.store(10, Ordering::SeqCst);
+
+		let uri = format!("http://localhost:3000/score?epoch={}", u64::MAX);
+		let req = Request::get(uri).body(Body::default()).unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(res.status(), BAD_REQUEST);
+		assert_eq!(*res.body(), ResponseBody::FutureEpoch.to_string());
+
+		// A past epoch without a proof is still just missing
+		let req = Request::get(Uri::from_static("http://localhost:3000/score?epoch=1"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(res.status(), NOT_FOUND);
+	}
+
+	#[tokio::test]
+	async fn should_deliver_signed_notification() {
+		let scores = vec![Scalar::from_u128(INITIAL_SCORE); NUM_NEIGHBOURS];
+		let (body, signature) = convergence_notification(Epoch(3), &scores);
+		let notification: serde_json::Value = serde_json::from_str(&body).unwrap();
+		assert_eq!(notification["epoch"], 3);
+		assert_eq!(
+			notification["scores"][0]["score"],
+			INITIAL_SCORE.to_string()
+		);
+
+		let parts: Vec<Scalar> = signature
+			.split(',')
+			.map(|x| {
+				let mut bytes = bytes_from_hex(x).unwrap();
+				bytes.reverse();
+				Scalar::from_bytes(&bytes.try_into().unwrap()).unwrap()
+			})
+			.collect();
+		let sig = Signature::new(parts[0], parts[1], parts[2]);
+		let message_hash = notification_message_hash(body.as_bytes());
+		assert!(verify(&sig, &server_key().public(), message_hash));
+
+		// The receiver fails the first delivery
+		let count = Arc::new(AtomicUsize::new(0));
+		let counter = count.clone();
+		let make_service = make_service_fn(move |_| {
+			let counter = counter.clone();
+			async move {
+				Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+					assert!(req.headers().contains_key(WEBHOOK_SIGNATURE_HEADER));
+					let status =
+						if counter.fetch_add(1, Ordering::SeqCst) == 0 { 500 } else { 200 };
+					let res = Response::builder().status(status).body(Body::empty()).unwrap();
+					async move { Ok::<_, Infallible>(res) }
+				}))
+			}
+		});
+		let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+		let url = format!("http://{}/hook", server.local_addr()).parse().unwrap();
+		spawn(server);
+
+		notify_webhook(url, body, signature).await;
+		assert_eq!(count.load(Ordering::SeqCst), 2);
+	}
+
+	#[tokio::test]
+	async fn should_query_epochs() {
+		let mut manager = proving_manager();
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(3)).unwrap();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/epochs"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(*res.body(), ResponseBody::Epochs(vec![3]).to_string());
+	}
+
+	#[tokio::test]
+	async fn should_validate_attestations() {
+		let mut manager = Manager::new_native_only();
+		manager.generate_initial_attestations();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/validate"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		let report = ValidationReport { untrusted: Vec::new() };
+		assert_eq!(*res.body(), ResponseBody::Validation(report).to_string());
+	}
+
+	#[tokio::test]
+	async fn should_query_score_history() {
+		let mut manager = proving_manager();
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let uri = format!(
+			"http://localhost:3000/score/history?pk={}&to=0",
+			public_key_to_bs58(&pks[0])
+		);
+		let req = Request::get(uri).body(Body::default()).unwrap();
+
+		let res = handle_request(req, arc_manager).await.unwrap();
+		let history = vec![ScoreEntry { epoch: 0, score: INITIAL_SCORE.to_string() }];
+		assert_eq!(*res.body(), to_string(&history).unwrap());
+	}
+
+	#[tokio::test]
+	async fn should_query_average_score() {
+		let mut manager = proving_manager();
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let pk = public_key_to_bs58(&pks[0]);
+		let uri = format!("http://localhost:3000/score/average?pk={}&epochs=3", pk);
+		let req = Request::get(uri).body(Body::default()).unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		let score = Score::new(0.2).unwrap();
+		let average = AverageScore { pk: pk.clone(), score, epochs: 1 };
+		assert_eq!(*res.body(), to_string(&average).unwrap());
+
+		let uri = format!("http://localhost:3000/score/average?pk={}&epochs=0", pk);
+		let req = Request::get(uri).body(Body::default()).unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(res.status(), BAD_REQUEST);
+	}
+
+	#[tokio::test]
+	async fn should_fail_ranking_diff_without_proofs() {
+		let arc_manager = Arc::new(Mutex::new(Manager::new_native_only()));
+
+		let req = Request::get(Uri::from_static(
+			"http://localhost:3000/ranking-diff?a=1&b=2",
+		))
+		.body(Body::default())
+		.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(res.status(), NOT_FOUND);
+		let e = EigenError::ProofNotFound { epoch: Some(1) };
+		assert_eq!(*res.body(), ResponseBody::Error(e).to_string());
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/ranking-diff?a=1"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(res.status(), BAD_REQUEST);
+	}
+
+	#[tokio::test]
+	async fn should_fail_score_history_with_invalid_pk() {
+		let manager = Manager::new_native_only();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let req = Request::get(Uri::from_static(
+			"http://localhost:3000/score/history?pk=invalid",
+		))
+		.body(Body::default())
+		.unwrap();
+
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(*res.body(), ResponseBody::InvalidQuery.to_string());
+	}
+
+	#[tokio::test]
+	async fn should_query_transcript() {
+		let mut manager = proving_manager();
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+		let proof = manager.get_proof(Epoch(0)).unwrap();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/score/transcript"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+
+		let transcript = bytes_to_hex(proof.transcript_bytes());
+		assert_eq!(
+			*res.body(),
+			ResponseBody::Transcript(transcript).to_string()
+		);
+
+		let req = Request::get(Uri::from_static(
+			"http://localhost:3000/score/calldata?chain=length-prefixed",
+		))
+		.body(Body::default())
+		.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+
+		let calldata = bytes_to_hex(&CalldataEncoding::LengthPrefixed.encode(&proof));
+		assert_eq!(*res.body(), ResponseBody::Calldata(calldata).to_string());
+	}
+
+	#[tokio::test]
+	async fn should_not_serve_proofs_when_proving_disabled() {
+		let arc_manager = Arc::new(Mutex::new(Manager::new_native_only()));
+		arc_manager.lock().unwrap().generate_initial_attestations();
+
+		for path in ["/score", "/score/transcript", "/score/calldata"] {
+			let uri: Uri = format!("http://localhost:3000{}", path).parse().unwrap();
+			let req = Request::get(uri).body(Body::default()).unwrap();
+			let res = handle_request(req, arc_manager.clone()).await.unwrap();
+			assert_eq!(res.status(), NOT_IMPLEMENTED);
+			assert_eq!(
+				*res.body(),
+				ResponseBody::Error(EigenError::ProvingDisabled).to_string()
+			);
+		}
+
+		// Missing attestations are still reported first
+		let mut manager = Manager::new_native_only();
+		for att in build_fixed_attestations().into_iter().take(3) {
+			manager.add_attestation(att).unwrap();
+		}
+		let arc_manager = Arc::new(Mutex::new(manager));
+		let req = Request::get(Uri::from_static("http://localhost:3000/score"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		let e = EigenError::IncompleteParticipation { submitted: 3, required: NUM_NEIGHBOURS };
+		assert_eq!(*res.body(), ResponseBody::Error(e).to_string());
+	}
+
+	#[tokio::test]
+	async fn should_reject_unknown_calldata_chain() {
+		let arc_manager = Arc::new(Mutex::new(Manager::new_native_only()));
+
+		let req = Request::get(Uri::from_static(
+			"http://localhost:3000/score/calldata?chain=unknown",
+		))
+		.body(Body::default())
+		.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(res.status(), BAD_REQUEST);
+		assert_eq!(*res.body(), ResponseBody::InvalidQuery.to_string());
+	}
+
+	#[tokio::test]
+	async fn should_query_normalized_scores() {
+		let mut manager = proving_manager();
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/score/normalized"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let score = Score::new(1.0 / NUM_NEIGHBOURS as f64).unwrap();
+		let scores: Vec<NormalizedScore> =
+			pks.iter().map(|pk| NormalizedScore { pk: public_key_to_bs58(pk), score }).collect();
+		assert_eq!(*res.body(), to_string(&scores).unwrap());
+
+		// Uniform scores are the same on a log scale
+		let req = Request::get(Uri::from_static(
+			"http://localhost:3000/score/normalized?scale=log",
+		))
+		.body(Body::default())
+		.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		let log_scores: Vec<serde_json::Value> = serde_json::from_str(res.body()).unwrap();
+		for (log_score, pk) in log_scores.iter().zip(&pks) {
+			assert_eq!(log_score["pk"], public_key_to_bs58(pk));
+			let value = log_score["score"].as_f64().unwrap();
+			assert!((value - 1.0 / NUM_NEIGHBOURS as f64).abs() < 1e-12);
+		}
+
+		let req = Request::get(Uri::from_static(
+			"http://localhost:3000/score/normalized?scale=sqrt",
+		))
+		.body(Body::default())
+		.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(res.status(), BAD_REQUEST);
+
+		// The values of the proof, before they are divided by their sum
+		let req = Request::get(Uri::from_static(
+			"http://localhost:3000/score/normalized?raw=true",
+		))
+		.body(Body::default())
+		.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		let raw_scores: Vec<RawScore> = pks
+			.iter()
+			.map(|pk| RawScore { pk: public_key_to_bs58(pk), score: INITIAL_SCORE.to_string() })
+			.collect();
+		assert_eq!(*res.body(), to_string(&raw_scores).unwrap());
+
+		let req = Request::get(Uri::from_static(
+			"http://localhost:3000/score/normalized?raw=true&scale=log",
+		))
+		.body(Body::default())
+		.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(res.status(), BAD_REQUEST);
+	}
 
 	#[tokio::test]
-	async fn should_fail_if_route_is_not_found() {
-		let mut rng = thread_rng();
-		let params = read_params(14);
-		let random_circuit =
-			EigenTrust::<NUM_NEIGHBOURS, NUM_ITER, INITIAL_SCORE, SCALE>::random(&mut rng);
-		let proving_key = keygen(&params, random_circuit).unwrap();
+	async fn should_compress_score_with_gzip() {
+		let mut manager = proving_manager();
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/score"))
+			.header(ACCEPT_ENCODING, "deflate, gzip;q=0.8")
+			.body(Body::default())
+			.unwrap();
+		assert!(accepts_gzip(&req));
+		let res = handle_request(req, arc_manager).await.unwrap();
+		let expected = res.body().clone();
+
+		let res = encode_response(res, true);
+		assert_eq!(res.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+		let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+		let mut decoded = String::new();
+		GzDecoder::new(&bytes[..]).read_to_string(&mut decoded).unwrap();
+		assert_eq!(decoded, expected);
+	}
+
+	#[test]
+	fn should_not_accept_gzip() {
+		let req = Request::get(Uri::from_static("http://localhost:3000/score"))
+			.header(ACCEPT_ENCODING, "gzip;q=0, deflate")
+			.body(Body::default())
+			.unwrap();
+		assert!(!accepts_gzip(&req));
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/score"))
+			.body(Body::default())
+			.unwrap();
+		assert!(!accepts_gzip(&req));
+	}
+
+	#[test]
+	fn should_pick_proving_threads() {
+		assert_eq!(proving_threads(Some(3)), 3);
+
+		let default_threads = proving_threads(None);
+		assert!(default_threads >= 1);
+		assert_eq!(proving_threads(Some(0)), default_threads);
+	}
+
+	#[test]
+	fn should_reject_attestation_during_convergence() {
+		let manager = Manager::new_native_only();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let att = build_fixed_attestations().remove(0);
+
+		CONVERGENCE_IN_PROGRESS.store(true, Ordering::SeqCst);
+		let res = handle_attestation(arc_manager.clone(), att.clone(), true);
+		CONVERGENCE_IN_PROGRESS.store(false, Ordering::SeqCst);
+		assert_eq!(res.unwrap_err(), EigenError::ConvergenceInProgress);
+		assert_eq!(arc_manager.lock().unwrap().attestation_count(), 0);
+
+		handle_attestation(arc_manager.clone(), att, true).unwrap();
+		assert_eq!(arc_manager.lock().unwrap().attestation_count(), 1);
+	}
+
+	#[tokio::test]
+	async fn should_query_status() {
+		let manager = Manager::new_native_only();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		EPOCH_INTERVAL.store(10, Ordering::SeqCst);
+		record_convergence(Epoch(3), Duration::from_millis(1500));
+		record_progress(ConvergenceProgress::ProofStarted);
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/status"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		let status: serde_json::Value = serde_json::from_str(res.body()).unwrap();
+		assert_eq!(status["progress"], "ProofStarted");
+		assert_eq!(status["participation"]["submitted"], 0);
+		assert_eq!(status["participation"]["required"], NUM_NEIGHBOURS);
+		assert_eq!(status["estimated_proving_ms"], 0);
+		assert_eq!(status["last_epoch"], 3);
+		assert_eq!(status["last_duration_ms"], 1500);
+		assert!(status["next_epoch_in_s"].as_u64().unwrap() <= 10);
+
+		record_progress_reset();
+		let req = Request::get(Uri::from_static("http://localhost:3000/status"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		let status: serde_json::Value = serde_json::from_str(res.body()).unwrap();
+		assert!(status["progress"].is_null());
+	}
+
+	#[tokio::test]
+	async fn should_verify_attestation_signature() {
+		let manager = Manager::new_native_only();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let mut att = build_fixed_attestations().remove(0);
+		let att_data = AttestationData::from(att.clone());
+		let req = Request::post(Uri::from_static("http://localhost:3000/verify-attestation"))
+			.body(Body::from(to_string(&att_data).unwrap()))
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(*res.body(), ResponseBody::SignatureValid(true).to_string());
+
+		att.scores[0] += Scalar::one();
+		let att_data = AttestationData::from(att);
+		let req = Request::post(Uri::from_static("http://localhost:3000/verify-attestation"))
+			.body(Body::from(to_string(&att_data).unwrap()))
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(*res.body(), ResponseBody::SignatureValid(false).to_string());
+	}
+
+	#[tokio::test]
+	async fn should_reject_attestation_over_record_limit() {
+		let arc_manager = Arc::new(Mutex::new(Manager::new_native_only()));
+
+		let req = Request::post(Uri::from_static("http://localhost:3000/verify-attestation"))
+			.body(Body::from(vec![b' '; MAX_RECORD_BYTES + 1]))
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(res.status(), PAYLOAD_TOO_LARGE);
+		assert_eq!(*res.body(), ResponseBody::PayloadTooLarge.to_string());
+	}
+
+	#[tokio::test]
+	async fn should_prove_only_with_admin_token() {
+		let arc_manager = Arc::new(Mutex::new(Manager::new_native_only()));
+		std::env::set_var(ADMIN_TOKEN_VAR, "secret");
+
+		let atts: Vec<AttestationData> =
+			build_fixed_attestations().into_iter().map(AttestationData::from).collect();
+		let req = Request::post(Uri::from_static("http://localhost:3000/prove"))
+			.body(Body::from(to_string(&atts).unwrap()))
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(res.status(), FORBIDDEN);
+
+		// Past the token, the native-only manager can't prove
+		let req = Request::post(Uri::from_static("http://localhost:3000/prove"))
+			.header(ADMIN_TOKEN_HEADER, "secret")
+			.body(Body::from(to_string(&atts).unwrap()))
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(res.status(), NOT_IMPLEMENTED);
+	}
+
+	#[tokio::test]
+	async fn should_parse_lenient_attestation() {
+		let manager = Manager::new_native_only();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		// A comment and a trailing comma, like in a hand-written attestation
+		let att = build_fixed_attestations().remove(0);
+		let att_json = to_string(&AttestationData::from(att)).unwrap();
+		let att_json5 = format!("// hand-written\n{},}}", &att_json[..att_json.len() - 1]);
+
+		let req = Request::post(Uri::from_static("http://localhost:3000/verify-attestation"))
+			.body(Body::from(att_json5.clone()))
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(res.status(), BAD_REQUEST);
+
+		let req = Request::post(Uri::from_static(
+			"http://localhost:3000/verify-attestation?lenient=true",
+		))
+		.body(Body::from(att_json5))
+		.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(*res.body(), ResponseBody::SignatureValid(true).to_string());
+	}
+
+	#[tokio::test]
+	async fn should_verify_submitted_proof() {
+		let mut manager = proving_manager();
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+		let mut proof = manager.get_proof(Epoch(0)).unwrap();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let proof_raw = to_string(&ProofRaw::from(proof.clone())).unwrap();
+		let req = Request::post(Uri::from_static("http://localhost:3000/verify"))
+			.body(Body::from(proof_raw))
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(*res.body(), ResponseBody::ProofValid(true).to_string());
+
+		proof.pub_ins[0] += Scalar::one();
+		let proof_raw = to_string(&ProofRaw::from(proof)).unwrap();
+		let req = Request::post(Uri::from_static("http://localhost:3000/verify"))
+			.body(Body::from(proof_raw))
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(*res.body(), ResponseBody::ProofValid(false).to_string());
+
+		// Public inputs that are not field elements
+		let proof_raw = ProofRaw { pub_ins: vec![[0xff; 32]], proof: Vec::new() };
+		let req = Request::post(Uri::from_static("http://localhost:3000/verify"))
+			.body(Body::from(to_string(&proof_raw).unwrap()))
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(res.status(), BAD_REQUEST);
+
+		// Too large, without declaring the content length
+		let req = Request::post(Uri::from_static("http://localhost:3000/verify"))
+			.body(Body::from(vec![b' '; MAX_PROOF_BYTES + 1]))
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(res.status(), PAYLOAD_TOO_LARGE);
+	}
+
+	#[tokio::test]
+	async fn should_replace_attestation() {
+		let manager = Manager::new_native_only();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let att = build_fixed_attestations().remove(0);
+		let att_data = to_string(&AttestationData::from(att)).unwrap();
+		for replaced in [false, true] {
+			let req = Request::post(Uri::from_static(
+				"http://localhost:3000/attestation/replace",
+			))
+			.body(Body::from(att_data.clone()))
+			.unwrap();
+			let res = handle_request(req, arc_manager.clone()).await.unwrap();
+			assert_eq!(*res.body(), ResponseBody::Replaced(replaced).to_string());
+		}
+		assert_eq!(arc_manager.lock().unwrap().attestation_count(), 1);
+
+		let req = Request::post(Uri::from_static(
+			"http://localhost:3000/attestation/replace",
+		))
+		.body(Body::from("{"))
+		.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(res.status(), BAD_REQUEST);
+	}
 
-		let manager = Manager::new(params, proving_key);
+	#[tokio::test]
+	async fn should_echo_request_id() {
+		let manager = Manager::new_native_only();
 		let arc_manager = Arc::new(Mutex::new(manager));
 
+		let req = Request::get(Uri::from_static("http://localhost:3000/status"))
+			.header(REQUEST_ID_HEADER, "client-id-1")
+			.body(Body::default())
+			.unwrap();
+		let res = handle_traced_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(res.headers().get(REQUEST_ID_HEADER).unwrap(), "client-id-1");
+
+		// Generated when missing, also for failed requests
 		let req = Request::get(Uri::from_static("http://localhost:3000/non_existing_route"))
 			.body(Body::default())
 			.unwrap();
+		let res = handle_traced_request(req, arc_manager).await.unwrap();
+		assert_eq!(res.status(), NOT_FOUND);
+		let id = res.headers().get(REQUEST_ID_HEADER).unwrap().to_str().unwrap();
+		assert_eq!(id.len(), 16);
+	}
+
+	#[tokio::test]
+	async fn should_replay_idempotent_submission() {
+		let manager = Manager::new_native_only();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let att = build_fixed_attestations().remove(0);
+		let att_data = to_string(&AttestationData::from(att)).unwrap();
+		let submissions = [
+			("should_replay_idempotent_submission-1", false),
+			("should_replay_idempotent_submission-1", false),
+			("should_replay_idempotent_submission-2", true),
+		];
+		for (key, replaced) in submissions {
+			let req = Request::post(Uri::from_static(
+				"http://localhost:3000/attestation/replace",
+			))
+			.header(IDEMPOTENCY_KEY_HEADER, key)
+			.body(Body::from(att_data.clone()))
+			.unwrap();
+			let res = handle_idempotent_request(req, arc_manager.clone()).await.unwrap();
+			assert_eq!(res.status(), 200);
+			assert_eq!(*res.body(), ResponseBody::Replaced(replaced).to_string());
+		}
+	}
+
+	#[tokio::test]
+	async fn should_tell_incomplete_and_empty_body_from_malformed() {
+		let manager = Manager::new_native_only();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		// The client disconnects in the middle of the upload
+		let (mut sender, body) = Body::channel();
+		spawn(async move {
+			sender.send_data("{\"pk\":".into()).await.unwrap();
+			sender.abort();
+		});
+		let req = Request::post(Uri::from_static("http://localhost:3000/verify-attestation"))
+			.body(body)
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(res.status(), REQUEST_TIMEOUT);
+		assert_eq!(*res.body(), ResponseBody::IncompleteBody.to_string());
+
+		let req = Request::post(Uri::from_static("http://localhost:3000/verify-attestation"))
+			.body(Body::from("{\"pk\":"))
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(res.status(), BAD_REQUEST);
+		assert_eq!(*res.body(), ResponseBody::InvalidQuery.to_string());
+
+		for body in ["", " \n"] {
+			let req = Request::post(Uri::from_static("http://localhost:3000/verify-attestation"))
+				.body(Body::from(body))
+				.unwrap();
+			let res = handle_request(req, arc_manager.clone()).await.unwrap();
+			assert_eq!(res.status(), BAD_REQUEST);
+			assert_eq!(*res.body(), ResponseBody::EmptyBody.to_string());
+		}
+	}
+
+	#[tokio::test]
+	async fn should_answer_after_deadline() {
+		let manager = Manager::new_native_only();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		// The client keeps the upload open without sending the rest
+		let (mut sender, body) = Body::channel();
+		sender.send_data("{\"pk\":".into()).await.unwrap();
+		let req = Request::post(Uri::from_static("http://localhost:3000/verify-attestation"))
+			.body(body)
+			.unwrap();
+		let deadline = Some(Duration::from_millis(50));
+		let res = handle_with_deadline(req, arc_manager.clone(), deadline).await.unwrap();
+		assert_eq!(res.status(), SERVICE_UNAVAILABLE);
+		assert_eq!(res.headers()[RETRY_AFTER], "5");
+		assert_eq!(*res.body(), ResponseBody::DeadlineExceeded.to_string());
+		drop(sender);
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/epochs"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_with_deadline(req, arc_manager, deadline).await.unwrap();
+		assert_eq!(res.status(), 200);
+	}
+
+	#[tokio::test]
+	async fn should_add_attestation_batch() {
+		let manager = Manager::new_native_only();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let mut atts = build_fixed_attestations();
+		atts[1].scores[0] += Scalar::one();
+		let records: Vec<String> = atts
+			.into_iter()
+			.take(3)
+			.map(|att| to_string(&AttestationData::from(att)).unwrap())
+			.collect();
+		let batch = format!("{}\n{}\n{}\n{{", records[0], records[1], records[2]);
+
+		// Send the batch in chunks that split the records
+		let (mut sender, body) = Body::channel();
+		let chunks: Vec<Vec<u8>> = batch.as_bytes().chunks(100).map(|x| x.to_vec()).collect();
+		spawn(async move {
+			for chunk in chunks {
+				sender.send_data(chunk.into()).await.unwrap();
+			}
+		});
+		let req = Request::post(Uri::from_static("http://localhost:3000/attestations"))
+			.body(body)
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+
+		let invalid_sig =
+			EigenError::InvalidAttestation { reason: "invalid signature".to_string() };
+		let incomplete = EigenError::InvalidAttestation { reason: "incomplete record".to_string() };
+		let results = vec![
+			BatchResult { index: 0, error: None },
+			BatchResult { index: 1, error: Some(invalid_sig) },
+			BatchResult { index: 2, error: None },
+			BatchResult { index: 3, error: Some(incomplete) },
+		];
+		assert_eq!(*res.body(), ResponseBody::BatchResults(results).to_string());
+		assert_eq!(arc_manager.lock().unwrap().attestation_count(), 2);
+
+		let req = Request::post(Uri::from_static("http://localhost:3000/attestations"))
+			.header(CONTENT_LENGTH, MAX_BATCH_BYTES + 1)
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(res.status(), PAYLOAD_TOO_LARGE);
+	}
+
+	#[tokio::test]
+	async fn should_prove_submitted_attestations() {
+		let manager = proving_manager();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let atts: Vec<AttestationData> =
+			build_fixed_attestations().into_iter().map(AttestationData::from).collect();
+		let req = Request::post(Uri::from_static("http://localhost:3000/prove"))
+			.body(Body::from(to_string(&atts).unwrap()))
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+
+		let proof: ProofRaw = serde_json::from_str(res.body()).unwrap();
+		let expected = Scalar::from_u128(INITIAL_SCORE).to_bytes();
+		assert_eq!(proof.pub_ins, vec![expected; NUM_NEIGHBOURS]);
+		assert_eq!(arc_manager.lock().unwrap().attestation_count(), 0);
 
+		let req = Request::post(Uri::from_static("http://localhost:3000/prove"))
+			.body(Body::from(to_string(&atts[1..]).unwrap()))
+			.unwrap();
 		let res = handle_request(req, arc_manager).await.unwrap();
-		assert_eq!(*res.body(), ResponseBody::InvalidRequest.to_string());
+		let reason = format!(
+			"expected {} attestations, got {}",
+			NUM_NEIGHBOURS,
+			NUM_NEIGHBOURS - 1
+		);
+		let err = EigenError::InvalidAttestation { reason };
+		assert_eq!(res.status(), BAD_REQUEST);
+		assert_eq!(*res.body(), ResponseBody::Error(err).to_string());
 	}
 
 	#[tokio::test]
-	async fn should_query_score() {
+	async fn should_query_scores_in_request_order() {
 		let mut rng = thread_rng();
-		let params = read_params(14);
-		let random_circuit =
-			EigenTrust::<NUM_NEIGHBOURS, NUM_ITER, INITIAL_SCORE, SCALE>::random(&mut rng);
-		let proving_key = keygen(&params, random_circuit).unwrap();
+		let mut manager = proving_manager();
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let non_member = SecretKey::random(&mut rng).public();
+		let uri = format!(
+			"http://localhost:3000/scores?pks={},{},{}",
+			public_key_to_bs58(&pks[1]),
+			public_key_to_bs58(&non_member),
+			public_key_to_bs58(&pks[3]),
+		);
+		let req = Request::get(uri).body(Body::default()).unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+
+		let score = INITIAL_SCORE.to_string();
+		let ordered_scores = OrderedScores {
+			epoch: 0,
+			scores: vec![Some(score.clone()), None, Some(score)],
+			found: vec![true, false, true],
+		};
+		assert_eq!(
+			*res.body(),
+			ResponseBody::OrderedScores(ordered_scores).to_string()
+		);
+	}
 
-		let mut manager = Manager::new(params, proving_key);
+	#[tokio::test]
+	async fn should_query_signed_score() {
+		let mut manager = proving_manager();
 		manager.generate_initial_attestations();
-		let epoch = Epoch(0);
-		manager.calculate_proofs(epoch).unwrap();
-		let real_proof = manager.get_proof(epoch).unwrap();
+		manager.calculate_proofs(Epoch(0)).unwrap();
 		let arc_manager = Arc::new(Mutex::new(manager));
 
-		let req = Request::get(Uri::from_static("http://localhost:3000/score"))
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let pk_bs58 = public_key_to_bs58(&pks[0]);
+		let uri = format!("http://localhost:3000/score/signed?pk={}&epoch=0", pk_bs58);
+		let req = Request::get(uri).body(Body::default()).unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+
+		let score = Scalar::from_u128(INITIAL_SCORE);
+		let message_hash = score_message_hash(&pks[0], Epoch(0), score);
+		let sig = sign(server_key(), &server_key().public(), message_hash);
+		let signed_score = SignedScore {
+			pk: pk_bs58,
+			epoch: 0,
+			score: INITIAL_SCORE.to_string(),
+			sig_r_x: scalar_to_hex(&sig.big_r.x),
+			sig_r_y: scalar_to_hex(&sig.big_r.y),
+			sig_s: scalar_to_hex(&sig.s),
+		};
+		assert_eq!(*res.body(), to_string(&signed_score).unwrap());
+	}
+
+	#[tokio::test]
+	async fn should_check_membership() {
+		let mut rng = thread_rng();
+		let manager = Manager::new_native_only();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let uri = format!(
+			"http://localhost:3000/is-member?pk={}",
+			public_key_to_bs58(&pks[0])
+		);
+		let req = Request::get(uri).body(Body::default()).unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(*res.body(), ResponseBody::IsMember(true).to_string());
+
+		let non_member = SecretKey::random(&mut rng).public();
+		let uri = format!(
+			"http://localhost:3000/is-member?pk={}",
+			public_key_to_bs58(&non_member)
+		);
+		let req = Request::get(uri).body(Body::default()).unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(*res.body(), ResponseBody::IsMember(false).to_string());
+
+		let req = Request::get(Uri::from_static(
+			"http://localhost:3000/is-member?pk=invalid",
+		))
+		.body(Body::default())
+		.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(*res.body(), ResponseBody::InvalidQuery.to_string());
+	}
+
+	#[tokio::test]
+	async fn should_query_metrics() {
+		let mut manager = proving_manager();
+		manager.generate_initial_attestations();
+		manager.set_max_cached_epochs(Some(1));
+		manager.calculate_proofs(Epoch(0)).unwrap();
+		manager.calculate_proofs(Epoch(1)).unwrap();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/metrics"))
 			.body(Body::default())
 			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		let lines: Vec<&str> = res.body().lines().filter(|x| !x.starts_with('#')).collect();
+		assert_eq!(lines[..3], [
+			"eigen_proof_cache_size 1", "eigen_proof_cache_capacity 1",
+			"eigen_proof_cache_evictions_total 1",
+		]);
+		// Equal scores spread the trust as much as possible
+		let entropy = lines[3].strip_prefix("eigen_score_entropy_bits ").unwrap();
+		let entropy: f64 = entropy.parse().unwrap();
+		assert!((entropy - (NUM_NEIGHBOURS as f64).log2()).abs() < 1e-12);
+	}
+
+	#[tokio::test]
+	async fn should_query_attestation_message() {
+		let manager = Manager::new_native_only();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let att = build_fixed_attestations().remove(0);
+		let (pks_hash, messages) =
+			calculate_message_hash::<NUM_NEIGHBOURS, 1>(att.neighbours.clone(), vec![att
+				.scores
+				.clone()]);
+		let neighbours: Vec<String> = att.neighbours.iter().map(public_key_to_bs58).collect();
+		let scores: Vec<String> = att.scores.iter().map(field_to_string).collect();
+		let uri = format!(
+			"http://localhost:3000/attestation/message?neighbours={}&scores={}",
+			neighbours.join(","),
+			scores.join(",")
+		);
+		let req = Request::get(uri).body(Body::default()).unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		let message: serde_json::Value = serde_json::from_str(res.body()).unwrap();
+		assert_eq!(message["neighbours"][0]["pk"], neighbours[0]);
+		assert_eq!(message["scores"][0], scores[0]);
+		assert_eq!(message["pks_hash"], scalar_to_hex(&pks_hash));
+		assert_eq!(message["message_hash"], scalar_to_hex(&messages[0]));
+		assert!(verify(&att.sig, &att.pk, messages[0]));
 
+		// Every neighbour needs a score
+		let uri = format!(
+			"http://localhost:3000/attestation/message?neighbours={}&scores=1",
+			neighbours.join(",")
+		);
+		let req = Request::get(uri).body(Body::default()).unwrap();
 		let res = handle_request(req, arc_manager).await.unwrap();
-		let proof_raw = ProofRaw::from(real_proof);
-		assert_eq!(*res.body(), to_string(&proof_raw).unwrap());
+		assert_eq!(res.status(), BAD_REQUEST);
+	}
+
+	#[tokio::test]
+	async fn should_query_pk_hash() {
+		let manager = Manager::new_native_only();
+		let arc_manager = Arc::new(Mutex::new(manager));
+
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let uri = format!(
+			"http://localhost:3000/pk-hash?pk={}",
+			public_key_to_bs58(&pks[0])
+		);
+		let req = Request::get(uri).body(Body::default()).unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		let pk_hash = scalar_to_hex(&hash_public_key(&pks[0]));
+		assert_eq!(
+			*res.body(),
+			ResponseBody::PkHash(pk_hash.clone()).to_string()
+		);
+
+		// Same hash for the hex encoding of the key
+		let [x, y] = pks[0].to_raw();
+		let uri = format!(
+			"http://localhost:3000/pk-hash?pk={}",
+			bytes_to_hex(&[x, y].concat())
+		);
+		let req = Request::get(uri).body(Body::default()).unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(*res.body(), ResponseBody::PkHash(pk_hash).to_string());
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/pk-hash?pk=invalid"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(res.status(), BAD_REQUEST);
+	}
+
+	#[tokio::test]
+	async fn should_query_coverage_with_admin_token() {
+		let mut manager = Manager::new_native_only();
+		manager.generate_initial_attestations();
+		let arc_manager = Arc::new(Mutex::new(manager));
+		std::env::set_var(ADMIN_TOKEN_VAR, "secret");
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/coverage"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(res.status(), FORBIDDEN);
+
+		let req = Request::get(Uri::from_static("http://localhost:3000/coverage"))
+			.header(ADMIN_TOKEN_HEADER, "secret")
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		let matrix = vec![vec![true; NUM_NEIGHBOURS]; NUM_NEIGHBOURS];
+		assert_eq!(*res.body(), to_string(&matrix).unwrap());
+	}
+
+	#[tokio::test]
+	async fn should_preview_distrust_with_admin_token() {
+		let mut manager = Manager::new_native_only();
+		manager.generate_initial_attestations();
+		let arc_manager = Arc::new(Mutex::new(manager));
+		std::env::set_var(ADMIN_TOKEN_VAR, "secret");
+
+		// The first participant fully distrusts the last one
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let mut distrust = vec![0; NUM_NEIGHBOURS];
+		distrust[4] = SCALE;
+		let body = format!(
+			r#"[{{"pk": "{}", "distrust": {:?}}}]"#,
+			public_key_to_bs58(&pks[0]),
+			distrust
+		);
+
+		let req = Request::post(Uri::from_static("http://localhost:3000/score/distrust"))
+			.body(Body::from(body.clone()))
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(res.status(), FORBIDDEN);
+
+		let req = Request::post(Uri::from_static("http://localhost:3000/score/distrust"))
+			.header(ADMIN_TOKEN_HEADER, "secret")
+			.body(Body::from(body))
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		let scores: Vec<serde_json::Value> = serde_json::from_str(res.body()).unwrap();
+		assert_eq!(scores[0]["pk"], public_key_to_bs58(&pks[0]));
+		assert!(scores[4]["score"].as_f64().unwrap() < 1e-9);
+
+		let req = Request::post(Uri::from_static("http://localhost:3000/score/distrust"))
+			.header(ADMIN_TOKEN_HEADER, "secret")
+			.body(Body::from(r#"[{"pk": "not-a-key", "distrust": []}]"#))
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		assert_eq!(res.status(), BAD_REQUEST);
+	}
+
+	#[tokio::test]
+	async fn should_reset_state_with_admin_token() {
+		let mut manager = Manager::new_native_only();
+		manager.generate_initial_attestations();
+		let arc_manager = Arc::new(Mutex::new(manager));
+		std::env::set_var(ADMIN_TOKEN_VAR, "secret");
+
+		let req = Request::post(Uri::from_static("http://localhost:3000/reset"))
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager.clone()).await.unwrap();
+		assert_eq!(*res.body(), ResponseBody::Unauthorized.to_string());
+
+		let req = Request::post(Uri::from_static("http://localhost:3000/reset"))
+			.header(ADMIN_TOKEN_HEADER, "secret")
+			.body(Body::default())
+			.unwrap();
+		let res = handle_request(req, arc_manager).await.unwrap();
+		let counts = ResetCounts { attestations: NUM_NEIGHBOURS, proofs: 0 };
+		assert_eq!(*res.body(), to_string(&counts).unwrap());
+	}
+
+	#[test]
+	fn should_reject_malformed_server_key() {
+		let (_, pk, sk_bs58, _) = generate_keypair();
+		std::env::set_var(SERVER_KEY_VAR, sk_bs58.join(","));
+		assert_eq!(read_server_key().unwrap().public(), pk);
+
+		std::env::set_var(SERVER_KEY_VAR, "not-a-key");
+		assert_eq!(read_server_key().err(), Some(EigenError::InvalidServerKey));
+		std::env::remove_var(SERVER_KEY_VAR);
+	}
+
+	#[tokio::test]
+	async fn should_serve_connection_in_memory() {
+		let arc_manager = Arc::new(Mutex::new(Manager::new_native_only()));
+		let (mut client, server) = duplex(64 * 1024);
+		let connection = spawn(handle_connection(server, arc_manager, true));
+
+		// Both requests are served on the kept-alive connection, which is closed
+		// after the second one
+		client
+			.write_all(
+				b"GET /epochs HTTP/1.1\r\nHost: localhost\r\n\r\n\
+				GET /missing HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+			)
+			.await
+			.unwrap();
+		let mut response = String::new();
+		client.read_to_string(&mut response).await.unwrap();
+		connection.await.unwrap();
+
+		let (first, second) = response.split_once("HTTP/1.1 404 Not Found\r\n").unwrap();
+		assert!(first.starts_with("HTTP/1.1 200 OK\r\n"));
+		assert!(first.to_lowercase().contains("content-length: 2\r\n"));
+		assert!(first.ends_with("\r\n\r\n[]"));
+		assert!(second.to_lowercase().contains("connection: close\r\n"));
 	}
 }
@@ -4,7 +4,21 @@ use eigen_trust_circuit::{
 };
 use serde::{Deserialize, Serialize};
 
-use super::NUM_NEIGHBOURS;
+use super::{NUM_NEIGHBOURS, SCALE};
+use crate::error::EigenError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+/// Scheme of the attestation signature, tagged on the wire with `scheme`
+pub enum SignatureScheme {
+	/// EdDSA over the embedded curve, the only scheme the circuit verifies
+	#[default]
+	#[serde(rename = "eddsa")]
+	EdDsa,
+	/// Any tag this version doesn't know, rejected when the attestation is
+	/// added instead of failing to parse
+	#[serde(other)]
+	Unsupported,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Raw data for the attestation
@@ -15,10 +29,14 @@ pub struct AttestationData {
 	pk: [[u8; 32]; 2],
 	neighbours: Vec<[[u8; 32]; 2]>,
 	scores: Vec<[u8; 32]>,
+	/// Missing in records from before the tag, which are all EdDSA
+	#[serde(default)]
+	scheme: SignatureScheme,
 }
 
 impl AttestationData {
-	/// Convert the struct into a vector of bytes
+	/// Convert the struct into a vector of bytes. The scheme is not part of
+	/// the bytes, since they are only used for EdDSA attestations.
 	pub fn to_bytes(self) -> Vec<u8> {
 		let mut bytes = Vec::new();
 		bytes.extend_from_slice(&self.sig_r_x);
@@ -76,7 +94,8 @@ impl AttestationData {
 			scores.push(score);
 		}
 
-		Self { sig_r_x, sig_r_y, sig_s, pk, neighbours, scores }
+		let scheme = SignatureScheme::EdDsa;
+		Self { sig_r_x, sig_r_y, sig_s, pk, neighbours, scores, scheme }
 	}
 }
 
@@ -88,8 +107,9 @@ impl From<Attestation> for AttestationData {
 		let pk_bytes = att.pk.to_raw();
 		let neighbours = att.neighbours.into_iter().map(|v| v.to_raw()).collect();
 		let scores = att.scores.into_iter().map(|v| v.to_bytes()).collect();
+		let scheme = att.scheme;
 
-		Self { sig_r_x, sig_r_y, sig_s, pk: pk_bytes, neighbours, scores }
+		Self { sig_r_x, sig_r_y, sig_s, pk: pk_bytes, neighbours, scores, scheme }
 	}
 }
 
@@ -104,15 +124,65 @@ pub struct Attestation {
 	pub neighbours: Vec<PublicKey>,
 	/// Scores for each of the neighbours
 	pub scores: Vec<Scalar>,
+	/// Scheme of the signature
+	pub scheme: SignatureScheme,
 }
 
 impl Attestation {
-	/// Construct a new attestation for given data
+	/// Construct a new EdDSA attestation for given data
 	pub fn new(
 		sig: Signature, pk: PublicKey, neighbours: Vec<PublicKey>, scores: Vec<Scalar>,
 	) -> Self {
-		Self { sig, pk, neighbours, scores }
+		Self { sig, pk, neighbours, scores, scheme: SignatureScheme::EdDsa }
+	}
+}
+
+/// Resolve the scores of an attestation where the sender abstains on some
+/// neighbours, given as `None`. Abstaining is not the same as a score of zero:
+/// a zero is an explicit lack of trust, while the part of `SCALE` left by the
+/// abstentions is redistributed among the rated neighbours, in proportion to
+/// their scores. Rated neighbours with zero keep zero. Rounding leftovers go
+/// to the largest remainders, so the scores sum up to `SCALE` exactly. Scores
+/// already summing up to `SCALE` or more are left as they are, with zero for
+/// the abstentions. Fails with `InvalidScore` if only zeros are left to
+/// redistribute to.
+pub fn resolve_abstentions(scores: &[Option<u128>]) -> Result<Vec<u128>, EigenError> {
+	let rated: Vec<u128> = scores.iter().map(|s| s.unwrap_or(0)).collect();
+	if scores.iter().all(Option::is_some) {
+		return Ok(rated);
+	}
+
+	let total: u128 = rated.iter().sum();
+	if total == 0 {
+		return Err(EigenError::InvalidScore);
+	}
+	if total >= SCALE {
+		return Ok(rated);
+	}
+
+	scale_scores(&rated)
+}
+
+/// Scale the scores to sum up to `SCALE`, keeping their proportions. Rounding
+/// leftovers go to the largest remainders, and to the first ones on ties.
+/// Fails with `InvalidScore` if the scores are all zero or too large to scale.
+pub fn scale_scores(scores: &[u128]) -> Result<Vec<u128>, EigenError> {
+	let total = scores.iter().try_fold(0u128, |acc, s| acc.checked_add(*s));
+	let total = total.filter(|total| *total > 0).ok_or(EigenError::InvalidScore)?;
+	let scaled = scores
+		.iter()
+		.map(|s| s.checked_mul(SCALE).ok_or(EigenError::InvalidScore))
+		.collect::<Result<Vec<u128>, EigenError>>()?;
+
+	let mut resolved: Vec<u128> = scaled.iter().map(|s| s / total).collect();
+	let mut remainders: Vec<(usize, u128)> = scaled.iter().map(|s| s % total).enumerate().collect();
+	remainders.sort_by(|(i, a), (j, b)| b.cmp(a).then(i.cmp(j)));
+
+	let distributed: u128 = resolved.iter().sum();
+	for (i, _) in remainders.iter().take((SCALE - distributed) as usize) {
+		resolved[*i] += 1;
 	}
+	Ok(resolved)
 }
 
 impl From<AttestationData> for Attestation {
@@ -132,7 +202,7 @@ impl From<AttestationData> for Attestation {
 			scores[i] = Scalar::from_bytes(n).unwrap();
 		}
 
-		Attestation { sig, pk, neighbours, scores }
+		Attestation { sig, pk, neighbours, scores, scheme: att.scheme }
 	}
 }
 
@@ -156,6 +226,7 @@ mod test {
 			pk,
 			neighbours: neighbours.clone(),
 			scores: scores.clone(),
+			scheme: SignatureScheme::EdDsa,
 		};
 		let att = Attestation::from(att_data);
 
@@ -165,5 +236,57 @@ mod test {
 		assert_eq!(att.sig.s.to_bytes(), sig_s);
 		assert_eq!(att.neighbours[0].clone().to_raw(), neighbours[0]);
 		assert_eq!(att.scores[0].clone().to_bytes(), scores[0]);
+		assert_eq!(att.scheme, SignatureScheme::EdDsa);
+	}
+
+	#[test]
+	fn should_parse_signature_scheme() {
+		let att = Attestation::new(
+			Signature::new(Scalar::zero(), Scalar::zero(), Scalar::zero()),
+			PublicKey::default(),
+			vec![PublicKey::default(); NUM_NEIGHBOURS],
+			vec![Scalar::zero(); NUM_NEIGHBOURS],
+		);
+		let mut json: serde_json::Value = serde_json::to_value(AttestationData::from(att)).unwrap();
+		assert_eq!(json["scheme"], "eddsa");
+
+		// Records from before the tag are EdDSA
+		json.as_object_mut().unwrap().remove("scheme");
+		let data: AttestationData = serde_json::from_value(json.clone()).unwrap();
+		assert_eq!(data.scheme, SignatureScheme::EdDsa);
+
+		json["scheme"] = "schnorr".into();
+		let data: AttestationData = serde_json::from_value(json).unwrap();
+		assert_eq!(data.scheme, SignatureScheme::Unsupported);
+	}
+
+	#[test]
+	fn should_resolve_abstentions() {
+		// Nothing to resolve without abstentions, zeros are kept
+		let scores = [Some(300), Some(0), Some(100), Some(300), Some(200)];
+		assert_eq!(resolve_abstentions(&scores).unwrap(), vec![
+			300, 0, 100, 300, 200
+		]);
+
+		// The abstained part goes to the rated neighbours, the zero stays zero
+		let scores = [Some(200), None, Some(200), Some(0), Some(400)];
+		assert_eq!(resolve_abstentions(&scores).unwrap(), vec![
+			250, 0, 250, 0, 500
+		]);
+
+		// Rounding leftovers go to the largest remainders
+		let scores = [Some(100), Some(100), Some(100), None, None];
+		assert_eq!(resolve_abstentions(&scores).unwrap(), vec![
+			334, 333, 333, 0, 0
+		]);
+
+		// Nothing left to redistribute
+		let scores = [Some(600), Some(400), None, None, None];
+		assert_eq!(resolve_abstentions(&scores).unwrap(), vec![
+			600, 400, 0, 0, 0
+		]);
+
+		let scores = [Some(0), None, None, None, None];
+		assert_eq!(resolve_abstentions(&scores), Err(EigenError::InvalidScore));
 	}
 }
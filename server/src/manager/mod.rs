@@ -7,12 +7,19 @@
 /// Attestation implementation
 pub mod attestation;
 
-use crate::{epoch::Epoch, error::EigenError, utils::keyset_from_raw};
-use attestation::Attestation;
+use crate::{
+	epoch::Epoch,
+	error::EigenError,
+	score::Score,
+	utils::{keyset_from_raw, public_key_to_bs58, scalar_to_hex, scalar_to_score, scalar_to_u128},
+};
+use attestation::{scale_scores, Attestation, AttestationData, SignatureScheme};
 use eigen_trust_circuit::{
 	calculate_message_hash,
-	circuit::{native, EigenTrust, PoseidonNativeHasher},
-	eddsa::native::{sign, verify as verify_sig, PublicKey},
+	circuit::{
+		native, native_sparse, EigenTrust, PoseidonNativeHasher, PoseidonNativeSponge, HASHER_WIDTH,
+	},
+	eddsa::native::{sign, verify as verify_sig, PublicKey, SecretKey},
 	halo2::{
 		halo2curves::{
 			bn256::{Bn256, Fr as Scalar, G1Affine},
@@ -22,20 +29,47 @@ use eigen_trust_circuit::{
 		plonk::ProvingKey,
 		poly::kzg::commitment::ParamsKZG,
 	},
-	utils::to_short,
-	verifier::{evm_verify, gen_evm_verifier, gen_proof},
-	Proof,
+	merkle_tree::native::MerkleTree,
+	params::poseidon_bn254_5x5::Params,
+	utils::{to_short, to_wide},
+	verifier::{evm_verify, gen_evm_verifier, gen_proof, try_evm_verify},
+	Proof, ProofRaw,
+};
+use ethers::utils::keccak256;
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::{HashMap, VecDeque},
+	io::{Read, Write},
+	time::{Duration, Instant},
 };
-use std::collections::HashMap;
 
 /// Number of iterations to run the eigen trust algorithm
 pub const NUM_ITER: usize = 10;
 /// Numbers of participants
 pub const NUM_NEIGHBOURS: usize = 5;
+const _: () = assert!(NUM_NEIGHBOURS > 0, "the participant set can't be empty");
 /// Initial score for each participant before the algorithms is run
 pub const INITIAL_SCORE: u128 = 1000;
 /// Scale for the scores to be computed inside the ZK circuit
 pub const SCALE: u128 = 1000;
+/// Pairing-friendly curve the proofs are generated over. The circuit, the
+/// EdDSA embedded curve (BabyJubJub), the poseidon params and the EVM verifier
+/// are all defined over its scalar field, so bn256 is the only supported
+/// instantiation. Changing the curve means changing all of them together.
+pub type ProvingCurve = Bn256;
+/// Affine form of the curve points used in the proving key commitments
+pub type ProvingAffine = G1Affine;
+/// The EigenTrust circuit, instantiated with the protocol parameters
+type EigenTrustCircuit = EigenTrust<NUM_NEIGHBOURS, NUM_ITER, INITIAL_SCORE, SCALE>;
+/// Number of power iterations used to estimate the convergence rate. The first
+/// half is a warm-up, and the rate is measured over the second half.
+const RATE_ITER: usize = 100;
+/// Estimates this close to one are treated as no convergence
+const RATE_TOLERANCE: f64 = 1e-6;
+/// Largest distance from one allowed for the sum of the normalized scores
+const NORMALIZATION_TOLERANCE: f64 = 1e-9;
+/// Number of the most recent proving runs averaged to estimate the next one
+const PROVING_SAMPLES: usize = 5;
 /// Temporary fixed set of participants
 pub const FIXED_SET: [[&str; 2]; NUM_NEIGHBOURS] = [
 	[
@@ -68,121 +102,839 @@ pub const PUBLIC_KEYS: [&str; NUM_NEIGHBOURS] = [
 	"Gz4dAnn3ex5Pq2vZQyJ94EqDdxpFaY74GJDFuuALvD6b",
 ];
 
+// Both coordinates of a public key have to fit in the inputs of the hasher
+const _: () = assert!(HASHER_WIDTH >= 2);
+
+/// Hash a public key with the poseidon hasher of the circuit, as the
+/// participants are identified by it. The coordinates are the first two
+/// inputs, and the rest are zeros. Changing the circuit's hasher means
+/// recomputing the `PUBLIC_KEYS`.
+pub fn hash_public_key(pk: &PublicKey) -> Scalar {
+	let mut inputs = [Scalar::zero(); HASHER_WIDTH];
+	inputs[0] = pk.0.x;
+	inputs[1] = pk.0.y;
+	PoseidonNativeHasher::new(inputs).permute()[0]
+}
+
+/// Calculate the message hash a score is signed over, binding the score to
+/// the participant public key and the epoch it was computed for
+pub fn score_message_hash(pk: &PublicKey, epoch: Epoch, score: Scalar) -> Scalar {
+	let inputs = [pk.0.x, pk.0.y, Scalar::from(epoch.0), score, Scalar::zero()];
+	PoseidonNativeHasher::new(inputs).permute()[0]
+}
+
+/// Calculate the message hash a webhook notification is signed over: the
+/// keccak-256 hash of its body, read as a little-endian integer and reduced
+/// into the field
+pub fn notification_message_hash(body: &[u8]) -> Scalar {
+	Scalar::from_bytes_wide(&to_wide(&keccak256(body)))
+}
+
+/// Build a signed attestation for every participant in the fixed set, giving
+/// equal scores to all neighbours
+fn fixed_attestations() -> Vec<Attestation> {
+	let (sks, pks) = keyset_from_raw(FIXED_SET);
+
+	let score = Scalar::from_u128(INITIAL_SCORE / NUM_NEIGHBOURS as u128);
+	let scores = vec![vec![score; NUM_NEIGHBOURS]; NUM_NEIGHBOURS];
+
+	const N: usize = NUM_NEIGHBOURS;
+	let (_, messages) = calculate_message_hash::<N, N>(pks.clone(), scores.clone());
+
+	let mut attestations = Vec::new();
+	for (((sk, pk), msg), scs) in sks.into_iter().zip(pks.clone()).zip(messages).zip(scores) {
+		let sig = sign(&sk, &pk, msg);
+		attestations.push(Attestation::new(sig, pk, pks.clone(), scs));
+	}
+	attestations
+}
+
+/// Check if two attestations have the same signature, neighbours and scores
+fn same_attestation(a: &Attestation, b: &Attestation) -> bool {
+	a.sig == b.sig && a.neighbours == b.neighbours && a.scores == b.scores
+}
+
+/// Run one iteration of the computation in `f64`, passing the score of each
+/// participant on to the others in proportion to its row of the trust matrix
+fn propagate(matrix: &[[f64; NUM_NEIGHBOURS]; NUM_NEIGHBOURS], scores: &[f64]) -> Vec<f64> {
+	let mut new_scores = vec![0.0; NUM_NEIGHBOURS];
+	for (row, s_i) in matrix.iter().zip(scores) {
+		for (new_s_j, op) in new_scores.iter_mut().zip(row) {
+			*new_s_j += op * s_i;
+		}
+	}
+	new_scores
+}
+
+/// Build a complete set of valid attestations for the fixed set, to be used
+/// as test data
+#[cfg(any(test, feature = "test-utils"))]
+pub fn build_fixed_attestations() -> Vec<Attestation> {
+	fixed_attestations()
+}
+
+/// Participants whose attestations differ between two managers, identified by
+/// their bs58 encoded public keys
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AttestationDiff {
+	/// Attestations only present in the current manager
+	pub added: Vec<String>,
+	/// Attestations only present in the other manager
+	pub removed: Vec<String>,
+	/// Attestations present in both, but with different contents
+	pub modified: Vec<String>,
+}
+
+/// Outcome of merging attestations into a manager, identified by the bs58
+/// encoded public keys of their senders
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MergeReport {
+	/// Attestations from senders that had none cached
+	pub added: Vec<String>,
+	/// Attestations identical to the cached ones
+	pub unchanged: Vec<String>,
+	/// Attestations that differ from the cached ones, which were kept
+	pub conflicts: Vec<String>,
+	/// Attestations that failed the validation, with their errors
+	pub rejected: Vec<EigenError>,
+}
+
+/// Change in the rank of a participant between two epochs. Rank `1` is the
+/// highest score, and participants with equal scores share a rank.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RankChange {
+	/// Bs58 encoded public key of the participant
+	pub pk: String,
+	/// Rank in the first epoch
+	pub rank_a: usize,
+	/// Rank in the second epoch
+	pub rank_b: usize,
+	/// Positions gained from the first epoch to the second, negative when
+	/// positions were lost
+	pub change: i64,
+}
+
+/// Milestones of a running convergence, reported in the order they are
+/// reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ConvergenceProgress {
+	/// The scores were calculated natively, to be used as the public inputs
+	NativeScoresDone,
+	/// The proving of the scores started, which takes most of the time
+	ProofStarted,
+	/// The proof was generated and cached
+	ProofDone,
+}
+
+/// Version of the binary snapshot format, written as its first byte. Has to
+/// be bumped whenever the saved state changes.
+pub const SNAPSHOT_VERSION: u8 = 1;
+
+/// Attestations and proofs of a manager, as they are saved
+#[derive(Serialize, Deserialize)]
+struct ManagerState {
+	attestations: Vec<AttestationData>,
+	proofs: Vec<(u64, ProofRaw)>,
+}
+
+/// Everything needed to make and verify the proofs of the circuit
+struct Prover {
+	params: ParamsKZG<ProvingCurve>,
+	proving_key: ProvingKey<ProvingAffine>,
+	verifier_code: Vec<u8>,
+}
+
 /// The peer struct.
 pub struct Manager {
 	pub(crate) cached_proofs: HashMap<Epoch, Proof>,
 	pub(crate) attestations: HashMap<Scalar, Attestation>,
-	params: ParamsKZG<Bn256>,
-	proving_key: ProvingKey<G1Affine>,
-	verifier_code: Vec<u8>,
+	prover: Option<Prover>,
+	max_cached_epochs: Option<usize>,
+	evictions: u64,
+	score_floor: f64,
+	strict_score_sum: bool,
+	proving_times: VecDeque<Duration>,
 }
 
 impl Manager {
 	/// Creates a new peer.
-	pub fn new(params: ParamsKZG<Bn256>, pk: ProvingKey<G1Affine>) -> Self {
+	pub fn new(params: ParamsKZG<ProvingCurve>, pk: ProvingKey<ProvingAffine>) -> Self {
 		let verifier_code = gen_evm_verifier(&params, &pk.get_vk(), vec![NUM_NEIGHBOURS]);
+		let prover = Prover { params, proving_key: pk, verifier_code };
+		Self { prover: Some(prover), ..Self::new_native_only() }
+	}
+
+	/// Creates a new peer without the proving key, skipping the key generation
+	/// and the verifier code generation. The attestations are accepted and the
+	/// scores are calculated natively like with a full manager, while making
+	/// or querying proofs fails with `ProvingDisabled`, and no proof passes
+	/// verification.
+	pub fn new_native_only() -> Self {
 		Self {
 			cached_proofs: HashMap::new(),
 			attestations: HashMap::new(),
-			params,
-			proving_key: pk,
-			verifier_code,
+			prover: None,
+			max_cached_epochs: None,
+			evictions: 0,
+			score_floor: 0.0,
+			strict_score_sum: false,
+			proving_times: VecDeque::new(),
 		}
 	}
 
-	/// Add a new attestation into the cache, by first calculating the hash of
-	/// the proving key
-	pub fn add_attestation(&mut self, att: Attestation) -> Result<(), EigenError> {
-		let group = PUBLIC_KEYS
+	/// Whether the manager can make proofs, `false` when constructed with
+	/// `new_native_only`
+	pub fn is_proving_enabled(&self) -> bool {
+		self.prover.is_some()
+	}
+
+	/// Limit the number of epochs with a cached proof, `None` for no limit.
+	/// When a new proof goes over the limit, the proofs of the oldest epochs
+	/// are evicted, since the latest ones are the ones served.
+	pub fn set_max_cached_epochs(&mut self, max_cached_epochs: Option<usize>) {
+		self.max_cached_epochs = max_cached_epochs;
+		self.evict_proofs();
+	}
+
+	/// Set the smallest normalized score a participant with some trust gets,
+	/// so tiny scores are not mistaken for no trust. Zero by default, which
+	/// keeps the scores as they are.
+	pub fn set_score_floor(&mut self, floor: Score) {
+		self.score_floor = floor.value();
+	}
+
+	/// Require the scores of every attestation to sum up to exactly `SCALE`,
+	/// rejecting the others with `InvalidScoreSum`. Every iteration divides
+	/// the opinions by `SCALE`, so each participant then hands out exactly the
+	/// score it holds, and the total score of the network stays at
+	/// `INITIAL_SCORE * NUM_NEIGHBOURS` whatever the attestations are. Off by
+	/// default, where an attestation with another total hands out more or
+	/// less trust than its sender holds, and the total drifts.
+	pub fn set_strict_score_sum(&mut self, strict: bool) {
+		self.strict_score_sum = strict;
+	}
+
+	/// Number of epochs with a cached proof, the limit on it, and the number of
+	/// proofs evicted to keep it
+	pub fn proof_cache_stats(&self) -> (usize, Option<usize>, u64) {
+		(
+			self.cached_proofs.len(),
+			self.max_cached_epochs,
+			self.evictions,
+		)
+	}
+
+	/// Estimate how long proving the next convergence takes, as the average of
+	/// the last proving runs of `calculate_proofs`. Zero until the first run
+	/// finishes.
+	pub fn estimate_proving_time(&self) -> Duration {
+		let runs = self.proving_times.len();
+		if runs == 0 {
+			return Duration::ZERO;
+		}
+		let total: Duration = self.proving_times.iter().sum();
+		total / runs as u32
+	}
+
+	/// Remember the duration of a proving run, forgetting the oldest one once
+	/// there are enough of them
+	fn record_proving_time(&mut self, duration: Duration) {
+		if self.proving_times.len() == PROVING_SAMPLES {
+			self.proving_times.pop_front();
+		}
+		self.proving_times.push_back(duration);
+	}
+
+	/// Evict the proofs of the oldest epochs until the cache is within the
+	/// limit
+	fn evict_proofs(&mut self) {
+		let limit = self.max_cached_epochs.unwrap_or(usize::MAX);
+		while self.cached_proofs.len() > limit {
+			// Over the limit, so the cache is never empty here
+			let oldest = *self.cached_proofs.keys().min_by_key(|epoch| epoch.0).unwrap();
+			self.cached_proofs.remove(&oldest);
+			self.evictions += 1;
+		}
+	}
+
+	/// Decode the public key hashes of the participant group
+	fn participant_group() -> [Scalar; NUM_NEIGHBOURS] {
+		PUBLIC_KEYS
 			.map(|x| bs58::decode(x).into_vec().unwrap())
 			.map(|x| to_short(&x))
-			.map(|x| Scalar::from_repr(x).unwrap());
+			.map(|x| Scalar::from_repr(x).unwrap())
+	}
 
-		let pk_hashes: Vec<Scalar> = att
-			.neighbours
-			.iter()
-			.map(|pk| {
-				let mut inps = [Scalar::zero(); 5];
-				inps[0] = pk.0.x;
-				inps[1] = pk.0.y;
-				let res = PoseidonNativeHasher::new(inps).permute()[0];
-				res
-			})
-			.collect();
+	/// Check whether the public key belongs to a member of the participant
+	/// group, by recomputing its hash
+	pub fn is_member(pk: &PublicKey) -> bool {
+		let pk_hash = hash_public_key(pk);
+		Self::participant_group().contains(&pk_hash)
+	}
+
+	/// Check that the public key hashes derived from the `FIXED_SET` secret
+	/// keys match the `PUBLIC_KEYS` constant, and that no participant appears
+	/// twice
+	pub fn verify_fixed_set_consistency() -> Result<(), EigenError> {
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		Self::verify_distinct_participants(&pks)?;
+
+		for (pk, pk_hash_raw) in pks.iter().zip(PUBLIC_KEYS) {
+			let pk_hash_bytes = bs58::decode(pk_hash_raw)
+				.into_vec()
+				.map_err(|_| EigenError::InconsistentFixedSet)?;
+			if pk_hash_bytes.len() > 32 {
+				return Err(EigenError::InconsistentFixedSet);
+			}
+			let expected: Option<Scalar> = Scalar::from_repr(to_short(&pk_hash_bytes)).into();
+
+			let pk_hash = hash_public_key(pk);
+
+			if expected != Some(pk_hash) {
+				return Err(EigenError::InconsistentFixedSet);
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Check that no public key appears twice in the participant set, failing
+	/// with `DuplicateParticipant` for the first repeated one. Attestations are
+	/// stored by the hash of their sender, so two slots with the same key would
+	/// silently overwrite each other's attestations.
+	pub fn verify_distinct_participants(pks: &[PublicKey]) -> Result<(), EigenError> {
+		for (i, pk) in pks.iter().enumerate() {
+			if pks[..i].contains(pk) {
+				return Err(EigenError::DuplicateParticipant { pk: public_key_to_bs58(pk) });
+			}
+		}
+		Ok(())
+	}
+
+	/// Add a new attestation into the cache, by first calculating the hash of
+	/// the proving key. An attestation cached for the same sender is replaced,
+	/// so a whole set can be added again without duplicating any opinions.
+	pub fn add_attestation(&mut self, att: Attestation) -> Result<(), EigenError> {
+		let pk_hash = self.validate_attestation(&att)?;
+		self.attestations.insert(pk_hash, att);
+
+		Ok(())
+	}
+
+	/// Add a set of attestations all at once, or none of them. Every
+	/// attestation is validated before any is added, and the first one failing
+	/// is reported with its position in the set and its sender.
+	pub fn add_attestation_set(&mut self, atts: Vec<Attestation>) -> Result<(), EigenError> {
+		let mut pk_hashes = Vec::new();
+		for (index, att) in atts.iter().enumerate() {
+			let pk_hash = self.validate_attestation(att).map_err(|e| {
+				let pk = public_key_to_bs58(&att.pk);
+				let reason = format!("attestation {} from {}: {}", index, pk, e);
+				EigenError::InvalidAttestation { reason }
+			})?;
+			pk_hashes.push(pk_hash);
+		}
+
+		self.attestations.extend(pk_hashes.into_iter().zip(atts));
+		Ok(())
+	}
+
+	/// Add the attestation in place of the one cached for its sender, if any.
+	/// Returns whether an attestation was replaced.
+	pub fn replace_attestation(&mut self, att: Attestation) -> Result<bool, EigenError> {
+		let pk_hash = self.validate_attestation(&att)?;
+		let replaced = self.attestations.insert(pk_hash, att).is_some();
+
+		Ok(replaced)
+	}
+
+	/// Check that the attestation comes from a participant, covers the whole
+	/// group and is signed, and in strict mode that its scores sum up to
+	/// `SCALE`. Returns the hash of the sender's public key.
+	fn validate_attestation(&self, att: &Attestation) -> Result<Scalar, EigenError> {
+		let group = Self::participant_group();
+
+		let pk_hashes: Vec<Scalar> = att.neighbours.iter().map(hash_public_key).collect();
 
 		if group.as_ref() != &pk_hashes {
-			return Err(EigenError::InvalidAttestation);
+			let reason = "neighbours don't match the participant group".to_string();
+			return Err(EigenError::InvalidAttestation { reason });
 		}
 
-		let mut pk_hash_inp = [Scalar::zero(); 5];
-		pk_hash_inp[0] = att.pk.0.x;
-		pk_hash_inp[1] = att.pk.0.y;
-		let res = PoseidonNativeHasher::new(pk_hash_inp).permute()[0];
+		let res = hash_public_key(&att.pk);
 
 		if !group.contains(&res) {
-			return Err(EigenError::InvalidAttestation);
+			let reason = "sender is not part of the participant group".to_string();
+			return Err(EigenError::InvalidAttestation { reason });
+		}
+
+		// A row of zero scores can't be normalized, so it would leave the
+		// participant's score undistributed
+		if att.scores.iter().all(|score| bool::from(score.is_zero())) {
+			return Err(EigenError::InvalidScore);
+		}
+
+		if self.strict_score_sum {
+			let sum = att.scores.iter().try_fold(0u128, |acc, score| {
+				scalar_to_u128(score).and_then(|score| acc.checked_add(score))
+			});
+			if sum != Some(SCALE) {
+				return Err(EigenError::InvalidScoreSum);
+			}
+		}
+
+		let valid = match att.scheme {
+			SignatureScheme::EdDsa => Self::verify_attestation_signature(att),
+			SignatureScheme::Unsupported => return Err(EigenError::UnsupportedSignatureScheme),
+		};
+		if !valid {
+			let reason = "invalid signature".to_string();
+			return Err(EigenError::InvalidAttestation { reason });
 		}
 
+		Ok(res)
+	}
+
+	/// Check the signature of the attestation over its neighbours and scores,
+	/// without checking the membership of the sender or the neighbours
+	pub fn verify_attestation_signature(att: &Attestation) -> bool {
 		let (_, message_hash) =
 			calculate_message_hash::<NUM_NEIGHBOURS, 1>(att.neighbours.clone(), vec![att
 				.scores
 				.clone()]);
 
-		if !verify_sig(&att.sig, &att.pk, message_hash[0]) {
-			return Err(EigenError::InvalidAttestation);
+		verify_sig(&att.sig, &att.pk, message_hash[0])
+	}
+
+	/// Get the attestation cached under the hash of the public key
+	pub fn get_attestation(&self, pk: &PublicKey) -> Result<&Attestation, EigenError> {
+		let res = hash_public_key(pk);
+		self.attestations
+			.get(&res)
+			.ok_or_else(|| EigenError::AttestationNotFound { pk: public_key_to_bs58(pk) })
+	}
+
+	/// Query the scores a participant gave to its neighbours in the cached
+	/// attestation, normalized to sum up to one and paired with the
+	/// neighbours' public keys
+	pub fn attested_scores(&self, pk: &PublicKey) -> Result<Vec<(PublicKey, f64)>, EigenError> {
+		let att = self.get_attestation(pk)?;
+		let scores = att
+			.scores
+			.iter()
+			.map(|s| scalar_to_u128(s).ok_or(EigenError::InvalidScore))
+			.collect::<Result<Vec<u128>, EigenError>>()?;
+
+		let total: u128 = scores.iter().sum();
+		if total == 0 {
+			return Err(EigenError::InvalidScore);
 		}
+		let attested = att
+			.neighbours
+			.iter()
+			.zip(scores)
+			.map(|(neighbour, s)| (*neighbour, s as f64 / total as f64))
+			.collect();
+		Ok(attested)
+	}
 
-		self.attestations.insert(res, att);
+	/// Find the participants that no cached attestation gives a score to, not
+	/// even their own, identified by their bs58 encoded public keys in the
+	/// order of the fixed set. Their score drops to zero after the first
+	/// iteration, which usually means the set is misconfigured.
+	pub fn untrusted_participants(&self) -> Vec<String> {
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		pks.iter()
+			.enumerate()
+			.filter(|(i, _)| {
+				self.attestations.values().all(|att| bool::from(att.scores[*i].is_zero()))
+			})
+			.map(|(_, pk)| public_key_to_bs58(pk))
+			.collect()
+	}
 
-		Ok(())
+	/// Which pairs of participants have an opinion, in the order of the fixed
+	/// set: row `i` has `true` at `j` if the attestation of `i` gives a
+	/// non-zero score to `j`. Participants without an attestation have a row
+	/// of `false`.
+	pub fn coverage_matrix(&self) -> Vec<Vec<bool>> {
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let mut matrix = vec![vec![false; NUM_NEIGHBOURS]; NUM_NEIGHBOURS];
+		for (row, pk) in matrix.iter_mut().zip(&pks) {
+			let att = match self.attestations.get(&hash_public_key(pk)) {
+				Some(att) => att,
+				None => continue,
+			};
+			for (neighbour, score) in att.neighbours.iter().zip(&att.scores) {
+				if let Ok(j) = Self::participant_index(neighbour) {
+					row[j] = !bool::from(score.is_zero());
+				}
+			}
+		}
+		matrix
 	}
 
-	/// Get the attestation cached under the hash of the public key
-	pub fn get_attestation(&self, pk: &PublicKey) -> Result<&Attestation, EigenError> {
-		let pk_hash_inp = [pk.0.x, pk.0.y, Scalar::zero(), Scalar::zero(), Scalar::zero()];
-		let res = PoseidonNativeHasher::new(pk_hash_inp).permute()[0];
-		self.attestations.get(&res).ok_or(EigenError::AttestationNotFound)
+	/// Number of attestations currently cached, to be used in the next
+	/// convergence
+	pub fn attestation_count(&self) -> usize {
+		self.attestations.len()
+	}
+
+	/// Number of participants with an attestation cached, and the number the
+	/// convergence requires, which is every participant of the fixed set
+	pub fn participation(&self) -> (usize, usize) {
+		(self.attestations.len(), NUM_NEIGHBOURS)
 	}
 
 	/// Generate initial attestations, since the circuit requires scores from
 	/// all participants in the fixed set
 	pub fn generate_initial_attestations(&mut self) {
-		let (sks, pks) = keyset_from_raw(FIXED_SET);
+		for att in fixed_attestations() {
+			let pk_hash = hash_public_key(&att.pk);
+			self.attestations.insert(pk_hash, att);
+		}
+	}
+
+	/// Calculate the scores from the current attestations, without generating a
+	/// proof. Uses the sparse native computation, so it is only a preview of
+	/// what the next proof will contain. Runs the given number of iterations,
+	/// capped at `NUM_ITER` since that is fixed in the circuit, and fewer
+	/// iterations give partially converged scores.
+	pub fn calculate_scores(&self, iterations: usize) -> Result<Vec<Scalar>, EigenError> {
+		let ops = self.opinions()?;
+		let init_score = vec![Scalar::from_u128(INITIAL_SCORE); NUM_NEIGHBOURS];
+		let iterations = iterations.min(NUM_ITER);
+		let scores = native_sparse::<Scalar, NUM_NEIGHBOURS, SCALE>(init_score, ops, iterations);
+		Ok(scores)
+	}
+
+	/// Calculate the normalized scores like `calculate_scores`, with the given
+	/// distrust of participants subtracted from their opinions. Each entry is
+	/// how much a participant distrusts each of the others, in the order of
+	/// the fixed set and in units of `SCALE` like the scores of its
+	/// attestation. A negative opinion takes away trust in proportion to the
+	/// score of the one giving it, so distrust from a trusted participant
+	/// weighs more. The trust a participant receives in an iteration is
+	/// clamped at zero, so distrust can take away all of it but never more,
+	/// and the scores are then normalized to sum up to one again. Computed in
+	/// `f64` only, since the circuit can't prove negative opinions.
+	///
+	/// Distrust is not signed by the participants, so it is only a preview for
+	/// the operator, and is never kept by the manager. Fails with
+	/// `ParticipantNotFound` or `DuplicateParticipant` for an entry of a key
+	/// outside the set or a repeated one, and with `InvalidScore` for distrust
+	/// that is not one value per participant summing up to at most `SCALE`, or
+	/// that takes away all the trust in the network.
+	pub fn calculate_scores_with_distrust(
+		&self, distrust: &[(PublicKey, Vec<u128>)], iterations: usize,
+	) -> Result<Vec<Score>, EigenError> {
+		let mut matrix = self.trust_matrix()?;
+		let mut seen = [false; NUM_NEIGHBOURS];
+		for (pk, distrust) in distrust {
+			let index = Self::participant_index(pk)?;
+			if seen[index] {
+				return Err(EigenError::DuplicateParticipant { pk: public_key_to_bs58(pk) });
+			}
+			seen[index] = true;
+
+			let sum = distrust.iter().try_fold(0u128, |sum, d| sum.checked_add(*d));
+			if distrust.len() != NUM_NEIGHBOURS || sum.map_or(true, |sum| sum > SCALE) {
+				return Err(EigenError::InvalidScore);
+			}
+			for (op, d) in matrix[index].iter_mut().zip(distrust) {
+				*op -= *d as f64 / SCALE as f64;
+			}
+		}
+
+		let mut scores = vec![1.0 / NUM_NEIGHBOURS as f64; NUM_NEIGHBOURS];
+		for _ in 0..iterations.min(NUM_ITER) {
+			let mut new_scores = propagate(&matrix, &scores);
+			for s in new_scores.iter_mut() {
+				*s = s.max(0.0);
+			}
+			let total: f64 = new_scores.iter().sum();
+			if total <= 0.0 {
+				return Err(EigenError::InvalidScore);
+			}
+			scores = new_scores.into_iter().map(|s| s / total).collect();
+		}
+
+		scores.into_iter().map(|s| Score::new(s.min(1.0))).collect()
+	}
 
-		let score = Scalar::from_u128(INITIAL_SCORE / NUM_NEIGHBOURS as u128);
-		let scores = vec![vec![score; NUM_NEIGHBOURS]; NUM_NEIGHBOURS];
+	/// Calculate the scores like `calculate_scores`, stopping early once no
+	/// score changes by more than `tolerance` in an iteration. Returns the
+	/// scores and the number of iterations used, which is at most `NUM_ITER`.
+	/// Using all of them hints that `NUM_ITER` is not enough for the scores
+	/// to converge to the tolerance. The changes are tracked in `f64`, with
+	/// the scores in units of `SCALE`. Only the preview stops early, the proof
+	/// always runs the full `NUM_ITER` iterations.
+	pub fn calculate_scores_until(
+		&self, tolerance: f64,
+	) -> Result<(Vec<Scalar>, usize), EigenError> {
+		let matrix = self.trust_matrix()?;
+		let mut scores = vec![INITIAL_SCORE as f64 / SCALE as f64; NUM_NEIGHBOURS];
+		let mut iterations = 0;
+		while iterations < NUM_ITER {
+			let new_scores = propagate(&matrix, &scores);
+			let delta =
+				new_scores.iter().zip(&scores).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+			scores = new_scores;
+			iterations += 1;
+			if delta <= tolerance {
+				break;
+			}
+		}
+
+		Ok((self.calculate_scores(iterations)?, iterations))
+	}
 
-		const N: usize = NUM_NEIGHBOURS;
-		let (_, messages) = calculate_message_hash::<N, N>(pks.clone(), scores.clone());
+	/// Run exactly `k` iterations of the computation in `f64`, starting from
+	/// `INITIAL_SCORE` for every participant, and return the scores in units
+	/// of `SCALE`. Unlike `calculate_scores_until`, it never stops early once
+	/// the scores converge, and unlike `calculate_scores`, `k` is not capped at
+	/// `NUM_ITER`. With `k = NUM_ITER` it gives the truncated result of the
+	/// circuit, up to the rounding of its fixed point arithmetic, and with a
+	/// larger `k` it shows how far that is from convergence.
+	pub fn run_iterations(&self, k: usize) -> Result<Vec<f64>, EigenError> {
+		let matrix = self.trust_matrix()?;
+		let mut scores = vec![INITIAL_SCORE as f64 / SCALE as f64; NUM_NEIGHBOURS];
+		for _ in 0..k {
+			scores = propagate(&matrix, &scores);
+		}
+		Ok(scores)
+	}
 
-		for (((sk, pk), msg), scs) in sks.into_iter().zip(pks.clone()).zip(messages).zip(scores) {
-			let sig = sign(&sk, &pk, msg);
+	/// Calculate the scores as seen by a single participant, for the same
+	/// number of iterations as `calculate_scores`. The global scores start
+	/// with `INITIAL_SCORE` for every participant, while here the whole initial
+	/// trust of the network is given to `from`, so the result is how much the
+	/// others are trusted along the paths starting from it. The total stays
+	/// the same as in the global scores. These scores are not part of the
+	/// proof.
+	pub fn personalized_scores(
+		&self, from: &PublicKey, iterations: usize,
+	) -> Result<Vec<Scalar>, EigenError> {
+		let index = Self::participant_index(from)?;
+		let ops = self.opinions()?;
+		let mut init_score = vec![Scalar::zero(); NUM_NEIGHBOURS];
+		init_score[index] = Scalar::from_u128(INITIAL_SCORE * NUM_NEIGHBOURS as u128);
+		let iterations = iterations.min(NUM_ITER);
+		let scores = native_sparse::<Scalar, NUM_NEIGHBOURS, SCALE>(init_score, ops, iterations);
+		Ok(scores)
+	}
 
-			let pk_hash_inp = [pk.0.x, pk.0.y, Scalar::zero(), Scalar::zero(), Scalar::zero()];
-			let pk_hash = PoseidonNativeHasher::new(pk_hash_inp).permute()[0];
+	/// Calculate how much the attestation of a participant changes the scores
+	/// of the current attestations, per participant in the order of the fixed
+	/// set. Both sets of scores are calculated like in `calculate_scores`, the
+	/// one without the attestation with the participant's opinions left out,
+	/// so its own score is not passed on to anyone.
+	pub fn marginal_impact(&self, pk: &PublicKey) -> Result<Vec<f64>, EigenError> {
+		self.get_attestation(pk)?;
+		let index = Self::participant_index(pk)?;
 
-			let att = Attestation::new(sig, pk, pks.clone(), scs);
-			self.attestations.insert(pk_hash, att);
+		let mut ops = self.opinions()?;
+		let init_score = vec![Scalar::from_u128(INITIAL_SCORE); NUM_NEIGHBOURS];
+		let with = native_sparse::<Scalar, NUM_NEIGHBOURS, SCALE>(
+			init_score.clone(),
+			ops.clone(),
+			NUM_ITER,
+		);
+		ops[index] = vec![Scalar::zero(); NUM_NEIGHBOURS];
+		let without = native_sparse::<Scalar, NUM_NEIGHBOURS, SCALE>(init_score, ops, NUM_ITER);
+
+		with.iter()
+			.zip(&without)
+			.map(|(with, without)| {
+				Ok(scalar_to_score(with, SCALE)? - scalar_to_score(without, SCALE)?)
+			})
+			.collect()
+	}
+
+	/// Estimate how fast the scores converge with the current attestations, as
+	/// the magnitude of the second largest eigenvalue of the row normalized
+	/// trust matrix. The distance to the converged scores shrinks by about
+	/// this factor on every iteration, so lower is faster, and zero means the
+	/// scores converge after the first iteration.
+	///
+	/// It is only an estimate, found by power iteration on vectors that sum to
+	/// zero, which leaves out the leading eigenvalue of one. Each of the
+	/// `NUM_NEIGHBOURS` starting vectors runs `RATE_ITER` iterations over the
+	/// dense matrix, so the cost is `O(RATE_ITER * NUM_NEIGHBOURS^3)`. Fails
+	/// with `NotConverging` when the estimate is one, which happens when the
+	/// trust graph is disconnected or periodic.
+	pub fn convergence_rate_estimate(&self) -> Result<f64, EigenError> {
+		let mut matrix = self.trust_matrix()?;
+		for row in matrix.iter_mut() {
+			let sum: f64 = row.iter().sum();
+			for op in row.iter_mut() {
+				*op /= sum;
+			}
+		}
+
+		let norm = |x: &[f64]| x.iter().map(|v| v * v).sum::<f64>().sqrt();
+		let mut rate: f64 = 0.0;
+		for start in 0..NUM_NEIGHBOURS {
+			let mut x = vec![-1.0 / NUM_NEIGHBOURS as f64; NUM_NEIGHBOURS];
+			x[start] += 1.0;
+
+			let mut log_growth = 0.0;
+			for k in 0..RATE_ITER {
+				let x_norm = norm(&x);
+				let unit_x: Vec<f64> = x.iter().map(|v| v / x_norm).collect();
+				let new_x = propagate(&matrix, &unit_x);
+				// Keep the sum at zero, so rounding errors don't bring back the
+				// leading eigenvalue
+				let mean = new_x.iter().sum::<f64>() / NUM_NEIGHBOURS as f64;
+				x = new_x.into_iter().map(|v| v - mean).collect();
+
+				let growth = norm(&x);
+				if growth == 0.0 {
+					log_growth = f64::NEG_INFINITY;
+					break;
+				}
+				if k >= RATE_ITER / 2 {
+					log_growth += growth.ln();
+				}
+			}
+			let start_rate = (log_growth / (RATE_ITER - RATE_ITER / 2) as f64).exp();
+			rate = rate.max(start_rate);
+		}
+
+		if rate >= 1.0 - RATE_TOLERANCE {
+			return Err(EigenError::NotConverging);
+		}
+		Ok(rate)
+	}
+
+	/// The opinions of every participant in the fixed set, in the order of the
+	/// set
+	fn opinions(&self) -> Result<Vec<Vec<Scalar>>, EigenError> {
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+
+		let mut ops = Vec::new();
+		for pk in pks {
+			let pk_hash = hash_public_key(&pk);
+			let att = self
+				.attestations
+				.get(&pk_hash)
+				.ok_or_else(|| EigenError::AttestationNotFound { pk: public_key_to_bs58(&pk) })?;
+			ops.push(att.scores.to_vec());
+		}
+		Ok(ops)
+	}
+
+	/// The opinions of every participant in the fixed set as shares of
+	/// `SCALE`, in the order of the set, for the computations in `f64`. Row
+	/// `i` holds how much participant `i` trusts each of the others.
+	fn trust_matrix(&self) -> Result<[[f64; NUM_NEIGHBOURS]; NUM_NEIGHBOURS], EigenError> {
+		let mut matrix = [[0.0; NUM_NEIGHBOURS]; NUM_NEIGHBOURS];
+		for (row, ops_i) in matrix.iter_mut().zip(self.opinions()?) {
+			for (trust, op) in row.iter_mut().zip(&ops_i) {
+				*trust = scalar_to_score(op, SCALE)?;
+			}
 		}
+		Ok(matrix)
 	}
 
 	/// Calculate the scores for the given epoch, and cache the ZK proof of them
 	pub fn calculate_proofs(&mut self, epoch: Epoch) -> Result<(), EigenError> {
-		let (_, pks) = keyset_from_raw(FIXED_SET);
+		self.calculate_proofs_with_progress(epoch, |_| {})
+	}
 
-		let pk_hashes: Vec<Scalar> = pks
-			.iter()
-			.map(|pk| {
-				let pk_hash_inp = [pk.0.x, pk.0.y, Scalar::zero(), Scalar::zero(), Scalar::zero()];
-				let pk_hash = PoseidonNativeHasher::new(pk_hash_inp).permute()[0];
-				pk_hash
-			})
-			.collect();
+	/// Same as `calculate_proofs`, calling `progress` as each milestone of the
+	/// convergence is reached
+	pub fn calculate_proofs_with_progress(
+		&mut self, epoch: Epoch, mut progress: impl FnMut(ConvergenceProgress),
+	) -> Result<(), EigenError> {
+		let (et, pub_ins) = Self::circuit_inputs(&self.attestations)?;
+		progress(ConvergenceProgress::NativeScoresDone);
+
+		progress(ConvergenceProgress::ProofStarted);
+		let start = Instant::now();
+		let proof = self.prove_circuit(et, pub_ins)?;
+		self.cache_proof(epoch, proof, start.elapsed());
+		progress(ConvergenceProgress::ProofDone);
+
+		Ok(())
+	}
+
+	/// Calculate the scores and the ZK proof of them for a complete set of
+	/// attestations, without touching the cached attestations and proofs
+	pub fn prove_attestations(&self, atts: Vec<Attestation>) -> Result<Proof, EigenError> {
+		if atts.len() != NUM_NEIGHBOURS {
+			let reason = format!(
+				"expected {} attestations, got {}",
+				NUM_NEIGHBOURS,
+				atts.len()
+			);
+			return Err(EigenError::InvalidAttestation { reason });
+		}
+
+		let mut attestations = HashMap::new();
+		for att in atts {
+			let pk_hash = self.validate_attestation(&att)?;
+			attestations.insert(pk_hash, att);
+		}
+		// Two attestations from the same participant leave another one out
+		if attestations.len() != NUM_NEIGHBOURS {
+			let reason = "duplicate attestations from the same participant".to_string();
+			return Err(EigenError::InvalidAttestation { reason });
+		}
+
+		self.prove(&attestations)
+	}
+
+	/// Time the native computation and the proving of the convergence
+	/// separately, and cache the proof like `calculate_proofs` does, recording
+	/// the proving time for `estimate_proving_time`. The proving time includes
+	/// the sanity check verification in debug builds.
+	#[cfg(feature = "bench")]
+	pub fn benchmark_convergence(
+		&mut self, epoch: Epoch,
+	) -> Result<(Duration, Duration), EigenError> {
+		let start = Instant::now();
+		let (et, pub_ins) = Self::circuit_inputs(&self.attestations)?;
+		let native_time = start.elapsed();
+
+		let start = Instant::now();
+		let proof = self.prove_circuit(et, pub_ins)?;
+		let proving_time = start.elapsed();
+
+		self.cache_proof(epoch, proof, proving_time);
+		Ok((native_time, proving_time))
+	}
+
+	/// Cache the proof of the epoch, evicting the oldest ones over the limit,
+	/// and remember how long it took to prove
+	fn cache_proof(&mut self, epoch: Epoch, proof: Proof, proving_time: Duration) {
+		self.record_proving_time(proving_time);
+		self.cached_proofs.insert(epoch, proof);
+		self.evict_proofs();
+	}
+
+	/// Generate the proof of the scores for the given attestations
+	fn prove(&self, attestations: &HashMap<Scalar, Attestation>) -> Result<Proof, EigenError> {
+		let (et, pub_ins) = Self::circuit_inputs(attestations)?;
+		self.prove_circuit(et, pub_ins)
+	}
+
+	/// Build the circuit for the given attestations, and calculate the scores
+	/// natively to be used as its public inputs. The attestations are taken in
+	/// the order of the fixed set, so the order they were added in never
+	/// changes the inputs.
+	fn circuit_inputs(
+		attestations: &HashMap<Scalar, Attestation>,
+	) -> Result<(EigenTrustCircuit, Vec<Scalar>), EigenError> {
+		let (_, pks) = keyset_from_raw(FIXED_SET);
 
 		let mut ops = Vec::new();
 		let mut sigs = Vec::new();
-		for pk_hash in pk_hashes {
-			let att = self.attestations.get(&pk_hash).unwrap();
+		for pk in &pks {
+			let pk_hash = hash_public_key(pk);
+			let att = attestations
+				.get(&pk_hash)
+				.ok_or_else(|| EigenError::AttestationNotFound { pk: public_key_to_bs58(pk) })?;
 			ops.push(att.scores.to_vec());
 			sigs.push(att.sig.clone());
 		}
@@ -195,63 +947,591 @@ impl Manager {
 		let init_score = vec![Scalar::from_u128(INITIAL_SCORE); NUM_NEIGHBOURS];
 		let pub_ins = native::<Scalar, NUM_NEIGHBOURS, NUM_ITER, SCALE>(init_score, ops);
 
-		let proof_bytes = gen_proof(&self.params, &self.proving_key, et, vec![pub_ins.clone()]);
+		Ok((et, pub_ins))
+	}
+
+	/// Generate the proof of the circuit for the given scores
+	fn prove_circuit(
+		&self, et: EigenTrustCircuit, pub_ins: Vec<Scalar>,
+	) -> Result<Proof, EigenError> {
+		let prover = self.prover.as_ref().ok_or(EigenError::ProvingDisabled)?;
+		let proof_bytes = gen_proof(&prover.params, &prover.proving_key, et, vec![
+			pub_ins.clone()
+		]);
 
 		// --- SANITY CHECK VERIFICATION ---
 		if cfg!(debug_assertions) {
 			evm_verify(
-				self.verifier_code.clone(),
+				prover.verifier_code.clone(),
 				vec![pub_ins.clone()],
 				proof_bytes.clone(),
 			);
 		}
 		// --- END ---
 
-		let proof = Proof { pub_ins, proof: proof_bytes };
-		self.cached_proofs.insert(epoch, proof);
-
-		Ok(())
+		Ok(Proof { pub_ins, proof: proof_bytes })
 	}
 
-	/// Query the proof for a given epoch
-	pub fn get_proof(&self, epoch: Epoch) -> Result<Proof, EigenError> {
-		self.cached_proofs.get(&epoch).ok_or(EigenError::ProofNotFound).cloned()
+	/// Compute the Merkle root committing to the cached attestations.
+	///
+	/// Leaves follow the `FIXED_SET` order. Each leaf is the poseidon sponge
+	/// hash of the canonical `AttestationData` bytes, read as 32-byte little
+	/// endian field elements. Participants without an attestation get a zero
+	/// leaf, and the leaves are zero-padded to the next power of two. Each
+	/// node is the poseidon hash of `[left, right, 0, 0, 0]`.
+	pub fn attestation_root(&self) -> Scalar {
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+
+		let leaves = pks
+			.iter()
+			.map(|pk| match self.get_attestation(pk) {
+				Ok(att) => {
+					let bytes = AttestationData::from(att.clone()).to_bytes();
+					let inputs: Vec<Scalar> = bytes
+						.chunks(32)
+						.map(|chunk| Scalar::from_bytes_wide(&to_wide(chunk)))
+						.collect();
+					let mut sponge = PoseidonNativeSponge::new();
+					sponge.update(&inputs);
+					sponge.squeeze()
+				},
+				Err(_) => Scalar::zero(),
+			})
+			.collect();
+
+		let height = NUM_NEIGHBOURS.next_power_of_two().trailing_zeros() as usize;
+		MerkleTree::<Scalar, Params>::build_tree(leaves, height).root()
 	}
 
-	/// Query the proof for the last epoch
-	pub fn get_last_proof(&self) -> Result<Proof, EigenError> {
-		let mut epoch = None;
-		for &curr_epoch in self.cached_proofs.keys() {
-			match epoch {
-				Some(e) => {
-					if curr_epoch > e {
-						epoch = Some(curr_epoch);
+	/// Compare the cached attestations against the ones of another manager,
+	/// treating the other one as the baseline
+	pub fn diff_attestations(&self, other: &Manager) -> AttestationDiff {
+		let mut diff = AttestationDiff::default();
+		for (pk_hash, att) in &self.attestations {
+			match other.attestations.get(pk_hash) {
+				None => diff.added.push(public_key_to_bs58(&att.pk)),
+				Some(other_att) => {
+					if !same_attestation(att, other_att) {
+						diff.modified.push(public_key_to_bs58(&att.pk));
 					}
 				},
+			}
+		}
+		for (pk_hash, att) in &other.attestations {
+			if !self.attestations.contains_key(pk_hash) {
+				diff.removed.push(public_key_to_bs58(&att.pk));
+			}
+		}
+
+		diff.added.sort();
+		diff.removed.sort();
+		diff.modified.sort();
+		diff
+	}
+
+	/// Merge attestations collected by another manager. Each of them goes
+	/// through the same validation as `add_attestation`. Attestations don't
+	/// carry an epoch, so there is no way to tell which of two different
+	/// attestations from the same sender is newer: on a conflict the cached
+	/// one is kept and the sender is reported, so it can be replaced
+	/// explicitly.
+	pub fn merge(&mut self, other_attestations: Vec<Attestation>) -> MergeReport {
+		let mut report = MergeReport::default();
+		for att in other_attestations {
+			let pk_hash = match self.validate_attestation(&att) {
+				Ok(pk_hash) => pk_hash,
+				Err(e) => {
+					report.rejected.push(e);
+					continue;
+				},
+			};
+			let pk = public_key_to_bs58(&att.pk);
+			match self.attestations.get(&pk_hash) {
 				None => {
-					epoch = Some(curr_epoch);
+					self.attestations.insert(pk_hash, att);
+					report.added.push(pk);
 				},
+				Some(cached) if same_attestation(cached, &att) => report.unchanged.push(pk),
+				Some(_) => report.conflicts.push(pk),
 			}
 		}
-		self.get_proof(epoch.unwrap())
+		report
 	}
-}
 
-#[cfg(test)]
-mod test {
-	use super::*;
-	use eigen_trust_circuit::{halo2::poly::commitment::ParamsProver, utils::keygen};
-	use rand::thread_rng;
+	/// Save the cached attestations and proofs as JSON, so they can be loaded
+	/// back with `load` instead of recalculating the proofs. The params and
+	/// keys are not saved.
+	pub fn save(&self, w: impl Write) -> Result<(), EigenError> {
+		serde_json::to_writer(w, &self.state()).map_err(|_| EigenError::InvalidState)
+	}
 
-	#[test]
-	fn should_calculate_proof() {
-		let mut rng = thread_rng();
-		let params = ParamsKZG::new(14);
+	/// Replace the cached attestations and proofs with the ones saved by
+	/// `save`. The attestations go through the same validation as
+	/// `add_attestation`. Nothing is replaced if any of them fails it, or if
+	/// the state can't be read.
+	pub fn load(&mut self, r: impl Read) -> Result<(), EigenError> {
+		let state: ManagerState =
+			serde_json::from_reader(r).map_err(|_| EigenError::InvalidState)?;
+		self.restore(state)
+	}
+
+	/// Save the same state as `save` in a compact binary snapshot, which is
+	/// the `SNAPSHOT_VERSION` byte followed by the bincode encoded state
+	pub fn export_snapshot(&self, mut w: impl Write) -> Result<(), EigenError> {
+		w.write_all(&[SNAPSHOT_VERSION]).map_err(|_| EigenError::InvalidState)?;
+		bincode::serialize_into(w, &self.state()).map_err(|_| EigenError::InvalidState)
+	}
+
+	/// Replace the cached attestations and proofs with the ones of a snapshot
+	/// saved by `export_snapshot`, like `load` does. Snapshots with a
+	/// different version are rejected with `InvalidState`.
+	pub fn import_snapshot(&mut self, mut r: impl Read) -> Result<(), EigenError> {
+		let mut version = [0u8; 1];
+		r.read_exact(&mut version).map_err(|_| EigenError::InvalidState)?;
+		if version[0] != SNAPSHOT_VERSION {
+			return Err(EigenError::InvalidState);
+		}
+		let state: ManagerState =
+			bincode::deserialize_from(r).map_err(|_| EigenError::InvalidState)?;
+		self.restore(state)
+	}
+
+	/// The cached attestations and proofs, with the proofs sorted by epoch
+	fn state(&self) -> ManagerState {
+		let attestations = self.attestations.values().cloned().map(AttestationData::from).collect();
+		let mut proofs: Vec<(u64, ProofRaw)> = self
+			.cached_proofs
+			.iter()
+			.map(|(epoch, proof)| (epoch.0, ProofRaw::from(proof.clone())))
+			.collect();
+		proofs.sort_by_key(|(epoch, _)| *epoch);
+
+		ManagerState { attestations, proofs }
+	}
+
+	/// Replace the cached attestations and proofs with the state, if all of
+	/// them are valid. Proofs need a score for every participant, and are
+	/// verified when the manager can make proofs. Proofs over the cache limit
+	/// are evicted, oldest first.
+	fn restore(&mut self, state: ManagerState) -> Result<(), EigenError> {
+		let mut attestations = HashMap::new();
+		for att_data in state.attestations {
+			let att = Attestation::from(att_data);
+			let pk_hash = self.validate_attestation(&att)?;
+			attestations.insert(pk_hash, att);
+		}
+		let mut cached_proofs = HashMap::new();
+		for (epoch, proof_raw) in state.proofs {
+			let proof = Proof::try_from(proof_raw).map_err(|_| EigenError::InvalidState)?;
+			if proof.pub_ins.len() != NUM_NEIGHBOURS {
+				return Err(EigenError::InvalidState);
+			}
+			if self.is_proving_enabled() && !self.verify_proof(&proof) {
+				return Err(EigenError::InvalidState);
+			}
+			cached_proofs.insert(Epoch(epoch), proof);
+		}
+
+		self.attestations = attestations;
+		self.cached_proofs = cached_proofs;
+		self.evict_proofs();
+		Ok(())
+	}
+
+	/// Remove all the cached attestations and proofs, and reset the eviction
+	/// count of the proof cache. Returns the number of attestations and proofs
+	/// removed. The params and keys are kept, along with the settings and the
+	/// recorded proving times, since those depend on the circuit and the
+	/// machine rather than on the state.
+	pub fn clear(&mut self) -> (usize, usize) {
+		let num_attestations = self.attestations.len();
+		let num_proofs = self.cached_proofs.len();
+		self.attestations.clear();
+		self.cached_proofs.clear();
+		self.evictions = 0;
+		(num_attestations, num_proofs)
+	}
+
+	/// Query the proof for a given epoch, verifying it against the EVM
+	/// verifier first. A proof that fails the verification is evicted from the
+	/// cache, so it is never served, and `InvalidProof` is returned.
+	pub fn get_verified_proof(&mut self, epoch: Epoch) -> Result<Proof, EigenError> {
+		let proof = self.get_proof(epoch)?;
+		if !self.verify_proof(&proof) {
+			self.cached_proofs.remove(&epoch);
+			return Err(EigenError::InvalidProof { epoch: epoch.0 });
+		}
+		Ok(proof)
+	}
+
+	/// Verify the proof against the EVM verifier of the circuit. Always fails
+	/// without a proving key.
+	pub fn verify_proof(&self, proof: &Proof) -> bool {
+		let prover = match &self.prover {
+			Some(prover) => prover,
+			None => return false,
+		};
+		let instances = vec![proof.pub_ins.clone()];
+		try_evm_verify(prover.verifier_code.clone(), instances, proof.proof.clone())
+	}
+
+	/// Check that the scores a proof claims are sane, on top of its
+	/// cryptographic verification, which only shows the computation was done
+	/// correctly. The scores always sum up to the initial total trust, so
+	/// there has to be one for every participant, each within
+	/// `[0, NUM_NEIGHBOURS * INITIAL_SCORE]`. Fails with `InvalidScore`
+	/// otherwise.
+	pub fn validate_proof_bounds(proof: &Proof) -> Result<(), EigenError> {
+		if proof.pub_ins.len() != NUM_NEIGHBOURS {
+			return Err(EigenError::InvalidScore);
+		}
+		let max = NUM_NEIGHBOURS as u128 * INITIAL_SCORE;
+		for score in &proof.pub_ins {
+			let score = scalar_to_u128(score).ok_or(EigenError::InvalidScore)?;
+			if score > max {
+				return Err(EigenError::InvalidScore);
+			}
+		}
+		Ok(())
+	}
+
+	/// Query the proof for a given epoch. Fails with `ProvingDisabled` without
+	/// a proving key.
+	pub fn get_proof(&self, epoch: Epoch) -> Result<Proof, EigenError> {
+		if self.prover.is_none() {
+			return Err(EigenError::ProvingDisabled);
+		}
+		let err = EigenError::ProofNotFound { epoch: Some(epoch.0) };
+		self.cached_proofs.get(&epoch).ok_or(err).cloned()
+	}
+
+	/// Find the position of the public key in the fixed set, which is also the
+	/// position of its score in the proof public inputs
+	fn participant_index(pk: &PublicKey) -> Result<usize, EigenError> {
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		pks.iter().position(|x| x == pk).ok_or(EigenError::ParticipantNotFound)
+	}
+
+	/// Query the score of a participant for a given epoch
+	pub fn get_score(&self, pk: &PublicKey, epoch: Epoch) -> Result<Scalar, EigenError> {
+		let index = Self::participant_index(pk)?;
+		let err = EigenError::ProofNotFound { epoch: Some(epoch.0) };
+		let proof = self.cached_proofs.get(&epoch).ok_or(err)?;
+		proof.pub_ins.get(index).copied().ok_or(EigenError::ParticipantNotFound)
+	}
+
+	/// Query the scores of the given participants for an epoch, in the same
+	/// order as the keys. Keys that are not part of the fixed set get `None`.
+	pub fn scores_for(
+		&self, pks: &[PublicKey], epoch: Epoch,
+	) -> Result<Vec<Option<Scalar>>, EigenError> {
+		let err = EigenError::ProofNotFound { epoch: Some(epoch.0) };
+		let proof = self.cached_proofs.get(&epoch).ok_or(err)?;
+		let scores = pks
+			.iter()
+			.map(|pk| {
+				let index = Self::participant_index(pk).ok()?;
+				proof.pub_ins.get(index).copied()
+			})
+			.collect();
+		Ok(scores)
+	}
+
+	/// Package the scores proven for the epoch as an attestation signed with
+	/// the given key, so they can be fed to another trust network. The
+	/// neighbours are the fixed set, and the scores are scaled to sum up to
+	/// `SCALE` like the ones of the participants. Another manager only accepts
+	/// it if the key is part of its participant group.
+	pub fn export_as_attestation(
+		&self, epoch: Epoch, sk: &SecretKey,
+	) -> Result<Attestation, EigenError> {
+		let err = EigenError::ProofNotFound { epoch: Some(epoch.0) };
+		let proof = self.cached_proofs.get(&epoch).ok_or(err)?;
+		let scores = proof
+			.pub_ins
+			.iter()
+			.map(|s| scalar_to_u128(s).ok_or(EigenError::InvalidScore))
+			.collect::<Result<Vec<u128>, EigenError>>()?;
+		let scores: Vec<Scalar> =
+			scale_scores(&scores)?.into_iter().map(Scalar::from_u128).collect();
+
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let (_, messages) =
+			calculate_message_hash::<NUM_NEIGHBOURS, 1>(pks.clone(), vec![scores.clone()]);
+		let pk = sk.public();
+		let sig = sign(sk, &pk, messages[0]);
+		Ok(Attestation::new(sig, pk, pks, scores))
+	}
+
+	/// Query the scores of all participants for a given epoch, normalized to
+	/// sum up to one. Scores are in the order of the fixed set. Non-zero
+	/// scores below the score floor are clamped to it, while zero stays zero,
+	/// so the sum can go over one by up to the floor for each participant.
+	pub fn normalized_scores(&self, epoch: Epoch) -> Result<Vec<Score>, EigenError> {
+		let err = EigenError::ProofNotFound { epoch: Some(epoch.0) };
+		let proof = self.cached_proofs.get(&epoch).ok_or(err)?;
+		let scores = proof
+			.pub_ins
+			.iter()
+			.map(|s| scalar_to_u128(s).ok_or(EigenError::InvalidScore))
+			.collect::<Result<Vec<u128>, EigenError>>()?;
+
+		let total: u128 = scores.iter().sum();
+		if total == 0 {
+			return Err(EigenError::InvalidScore);
+		}
+		scores
+			.into_iter()
+			.map(|s| {
+				let value = s as f64 / total as f64;
+				let floored = if s > 0 { value.max(self.score_floor) } else { value };
+				Score::new(floored)
+			})
+			.collect()
+	}
+
+	/// Shannon entropy, in bits, of the distribution of the scores in the proof
+	/// of the epoch, as a measure of how spread out the trust is. It is
+	/// `log2(NUM_NEIGHBOURS)` when every participant has the same score, and
+	/// drops towards zero as the trust concentrates on fewer of them, which
+	/// can be a sign of a successful Sybil attack or collusion. Computed from
+	/// the shares of the total score, before the score floor is applied.
+	pub fn score_entropy(&self, epoch: Epoch) -> Result<f64, EigenError> {
+		let scores = self
+			.public_inputs(epoch)?
+			.iter()
+			.map(|s| scalar_to_u128(s).ok_or(EigenError::InvalidScore))
+			.collect::<Result<Vec<u128>, EigenError>>()?;
+
+		let total: u128 = scores.iter().sum();
+		if total == 0 {
+			return Err(EigenError::InvalidScore);
+		}
+		let entropy = scores
+			.into_iter()
+			.filter(|s| *s > 0)
+			.map(|s| {
+				let p = s as f64 / total as f64;
+				-p * p.log2()
+			})
+			.sum();
+		Ok(entropy)
+	}
+
+	/// Same as `normalized_scores`, but also checks that the scores sum up to
+	/// one within `NORMALIZATION_TOLERANCE`, so rounding errors of the float
+	/// conversion never reach the output unnoticed. The sum may go over by
+	/// the score floor for each participant too. Fails with `InvalidScore`
+	/// otherwise.
+	pub fn checked_normalized_scores(&self, epoch: Epoch) -> Result<Vec<Score>, EigenError> {
+		let scores = self.normalized_scores(epoch)?;
+		let sum: f64 = scores.iter().map(Score::value).sum();
+		let max_sum = 1.0 + NORMALIZATION_TOLERANCE + NUM_NEIGHBOURS as f64 * self.score_floor;
+		if sum < 1.0 - NORMALIZATION_TOLERANCE || sum > max_sum {
+			return Err(EigenError::InvalidScore);
+		}
+		Ok(scores)
+	}
+
+	/// Same as `checked_normalized_scores`, on a logarithmic scale that is
+	/// easier to read when the scores span orders of magnitude. Each
+	/// normalized score `p` is mapped to `ln(1 + N * p)`, with `N` the number
+	/// of participants, and the results are normalized to sum up to one again.
+	/// Zero stays zero, and the order of the scores is kept.
+	pub fn normalized_scores_log(&self, epoch: Epoch) -> Result<Vec<Score>, EigenError> {
+		let scores = self.checked_normalized_scores(epoch)?;
+		let logs: Vec<f64> =
+			scores.iter().map(|s| (NUM_NEIGHBOURS as f64 * s.value()).ln_1p()).collect();
+		let total: f64 = logs.iter().sum();
+		if total == 0.0 {
+			return Err(EigenError::InvalidScore);
+		}
+		logs.into_iter().map(|s| Score::new(s / total)).collect()
+	}
+
+	/// Average the normalized score of a participant over the last `epochs`
+	/// cached epochs, to smooth out the changes between single epochs. Fewer
+	/// epochs are averaged when fewer are cached, so the number of averaged
+	/// epochs is returned with the mean. Fails with `ProofNotFound` if there
+	/// is no epoch to average.
+	pub fn average_normalized_score(
+		&self, pk: &PublicKey, epochs: usize,
+	) -> Result<(Score, usize), EigenError> {
+		let index = Self::participant_index(pk)?;
+		let available = self.available_epochs();
+		let last = &available[available.len().saturating_sub(epochs)..];
+		if last.is_empty() {
+			return Err(EigenError::ProofNotFound { epoch: None });
+		}
+
+		let mut sum = 0.0;
+		for epoch in last {
+			sum += self.normalized_scores(*epoch)?[index].value();
+		}
+		let mean = Score::new(sum / last.len() as f64)?;
+		Ok((mean, last.len()))
+	}
+
+	/// Rank the participants by their scores in the proof of the epoch, in the
+	/// order of the fixed set. A participant's rank is one more than the
+	/// number of participants with a higher score.
+	fn ranks(&self, epoch: Epoch) -> Result<Vec<usize>, EigenError> {
+		let scores = self
+			.public_inputs(epoch)?
+			.iter()
+			.map(|s| scalar_to_u128(s).ok_or(EigenError::InvalidScore))
+			.collect::<Result<Vec<u128>, EigenError>>()?;
+		let ranks = scores.iter().map(|s| 1 + scores.iter().filter(|other| *other > s).count());
+		Ok(ranks.collect())
+	}
+
+	/// Compare the rankings of the participants between the epochs `a` and
+	/// `b`, from the scores in their cached proofs, in the order of the fixed
+	/// set. Fails with `ProofNotFound` if either epoch has no proof.
+	pub fn ranking_diff(&self, a: Epoch, b: Epoch) -> Result<Vec<RankChange>, EigenError> {
+		let ranks_a = self.ranks(a)?;
+		let ranks_b = self.ranks(b)?;
+
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let changes = pks
+			.iter()
+			.zip(ranks_a.into_iter().zip(ranks_b))
+			.map(|(pk, (rank_a, rank_b))| RankChange {
+				pk: public_key_to_bs58(pk),
+				rank_a,
+				rank_b,
+				change: rank_a as i64 - rank_b as i64,
+			})
+			.collect();
+		Ok(changes)
+	}
+
+	/// Query the scores of a participant from the cached proofs, for all
+	/// epochs inside the optional `[from, to]` range, sorted by epoch
+	pub fn get_score_history(
+		&self, pk: &PublicKey, from: Option<Epoch>, to: Option<Epoch>,
+	) -> Result<Vec<(Epoch, Scalar)>, EigenError> {
+		let index = Self::participant_index(pk)?;
+
+		let mut history: Vec<(Epoch, Scalar)> = self
+			.cached_proofs
+			.iter()
+			.filter(|(epoch, _)| from.map_or(true, |f| f.0 <= epoch.0))
+			.filter(|(epoch, _)| to.map_or(true, |t| epoch.0 <= t.0))
+			.filter_map(|(epoch, proof)| Some((*epoch, *proof.pub_ins.get(index)?)))
+			.collect();
+		history.sort_by_key(|(epoch, _)| epoch.0);
+
+		Ok(history)
+	}
+
+	/// Query the epochs that have a cached proof, sorted in ascending order
+	pub fn available_epochs(&self) -> Vec<Epoch> {
+		let mut epochs: Vec<Epoch> = self.cached_proofs.keys().copied().collect();
+		epochs.sort_by_key(|epoch| epoch.0);
+		epochs
+	}
+
+	/// Query the last epoch that has a cached proof
+	pub fn get_last_epoch(&self) -> Result<Epoch, EigenError> {
+		let mut epoch = None;
+		for &curr_epoch in self.cached_proofs.keys() {
+			match epoch {
+				Some(e) => {
+					if curr_epoch > e {
+						epoch = Some(curr_epoch);
+					}
+				},
+				None => {
+					epoch = Some(curr_epoch);
+				},
+			}
+		}
+		epoch.ok_or(EigenError::ProofNotFound { epoch: None })
+	}
+
+	/// Query the public inputs of the proof for a given epoch. These are the
+	/// converged scores, in the order expected by the verifier.
+	pub fn public_inputs(&self, epoch: Epoch) -> Result<Vec<Scalar>, EigenError> {
+		self.cached_proofs
+			.get(&epoch)
+			.map(|proof| proof.pub_ins.clone())
+			.ok_or(EigenError::ProofNotFound { epoch: Some(epoch.0) })
+	}
+
+	/// Query the public inputs of the proof for a given epoch, as `0x`-prefixed
+	/// big-endian hex strings, matching their `uint256` calldata encoding.
+	pub fn public_inputs_hex(&self, epoch: Epoch) -> Result<Vec<String>, EigenError> {
+		let pub_ins = self.public_inputs(epoch)?;
+		Ok(pub_ins.iter().map(scalar_to_hex).collect())
+	}
+
+	/// Query the proof for the last epoch
+	pub fn get_last_proof(&self) -> Result<Proof, EigenError> {
+		self.get_proof(self.get_last_epoch()?)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use eigen_trust_circuit::{halo2::poly::commitment::ParamsProver, utils::keygen};
+	use rand::thread_rng;
+
+	/// Manager with a proving key, for the tests that make or verify proofs
+	fn proving_manager(params: ParamsKZG<ProvingCurve>) -> Manager {
 		let random_circuit =
-			EigenTrust::<NUM_NEIGHBOURS, NUM_ITER, INITIAL_SCORE, SCALE>::random(&mut rng);
+			EigenTrust::<NUM_NEIGHBOURS, NUM_ITER, INITIAL_SCORE, SCALE>::random(&mut thread_rng());
 		let proving_key = keygen(&params, random_circuit).unwrap();
+		Manager::new(params, proving_key)
+	}
+
+	#[test]
+	fn should_have_consistent_fixed_set() {
+		assert_eq!(Manager::verify_fixed_set_consistency(), Ok(()));
+	}
+
+	#[test]
+	fn should_estimate_proving_time() {
+		let mut manager = Manager::new_native_only();
+		assert_eq!(manager.estimate_proving_time(), Duration::ZERO);
+
+		manager.record_proving_time(Duration::from_secs(2));
+		manager.record_proving_time(Duration::from_secs(4));
+		assert_eq!(manager.estimate_proving_time(), Duration::from_secs(3));
 
-		let mut manager = Manager::new(params, proving_key);
+		// Only the most recent runs are averaged
+		for _ in 0..PROVING_SAMPLES {
+			manager.record_proving_time(Duration::from_secs(10));
+		}
+		assert_eq!(manager.estimate_proving_time(), Duration::from_secs(10));
+	}
+
+	#[test]
+	fn should_reject_duplicate_participant() {
+		let (_, mut pks) = keyset_from_raw(FIXED_SET);
+		assert_eq!(Manager::verify_distinct_participants(&pks), Ok(()));
+
+		pks[3] = pks[1];
+		let pk = public_key_to_bs58(&pks[1]);
+		assert_eq!(
+			Manager::verify_distinct_participants(&pks),
+			Err(EigenError::DuplicateParticipant { pk })
+		);
+	}
+
+	#[test]
+	fn should_check_membership() {
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		assert!(pks.iter().all(Manager::is_member));
+
+		let sk = SecretKey::random(&mut thread_rng());
+		assert!(!Manager::is_member(&sk.public()));
+	}
+
+	#[test]
+	fn should_calculate_proof() {
+		let mut manager = proving_manager(ParamsKZG::new(14));
 
 		manager.generate_initial_attestations();
 		let epoch = Epoch(0);
@@ -260,4 +1540,1120 @@ mod test {
 		let scores = [Scalar::from_u128(INITIAL_SCORE); NUM_NEIGHBOURS];
 		assert_eq!(proof.pub_ins, scores);
 	}
+
+	#[test]
+	fn should_report_convergence_progress() {
+		let mut manager = proving_manager(ParamsKZG::new(14));
+		let mut milestones = Vec::new();
+		let res = manager.calculate_proofs_with_progress(Epoch(0), |p| milestones.push(p));
+		assert!(matches!(res, Err(EigenError::AttestationNotFound { .. })));
+		assert!(milestones.is_empty());
+
+		manager.generate_initial_attestations();
+		manager.calculate_proofs_with_progress(Epoch(0), |p| milestones.push(p)).unwrap();
+		let expected = vec![
+			ConvergenceProgress::NativeScoresDone,
+			ConvergenceProgress::ProofStarted,
+			ConvergenceProgress::ProofDone,
+		];
+		assert_eq!(milestones, expected);
+		assert!(manager.get_proof(Epoch(0)).is_ok());
+	}
+
+	#[test]
+	fn should_list_available_epochs() {
+		let mut manager = proving_manager(ParamsKZG::new(14));
+		assert_eq!(manager.available_epochs(), vec![]);
+
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(5)).unwrap();
+		manager.calculate_proofs(Epoch(2)).unwrap();
+		assert_eq!(manager.available_epochs(), vec![Epoch(2), Epoch(5)]);
+	}
+
+	#[test]
+	fn should_average_normalized_score() {
+		let mut manager = Manager::new_native_only();
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let res = manager.average_normalized_score(&pks[0], 3);
+		assert_eq!(res, Err(EigenError::ProofNotFound { epoch: None }));
+
+		let rows = [[5000, 0, 0, 0, 0], [0, 5000, 0, 0, 0], [2500, 2500, 0, 0, 0]];
+		for (epoch, row) in rows.iter().enumerate() {
+			let pub_ins = row.iter().map(|s| Scalar::from_u128(*s)).collect();
+			let proof = Proof { pub_ins, proof: Vec::new() };
+			manager.cached_proofs.insert(Epoch(epoch as u64 + 1), proof);
+		}
+
+		let (mean, count) = manager.average_normalized_score(&pks[0], 2).unwrap();
+		assert_eq!((mean.value(), count), (0.25, 2));
+
+		// Only the cached epochs are averaged
+		let (mean, count) = manager.average_normalized_score(&pks[0], 10).unwrap();
+		assert_eq!((mean.value(), count), (0.5, 3));
+	}
+
+	#[test]
+	fn should_calculate_scores_with_distrust() {
+		let mut manager = Manager::new_native_only();
+		manager.generate_initial_attestations();
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+
+		let scores = manager.calculate_scores_with_distrust(&[], NUM_ITER).unwrap();
+		for score in &scores {
+			assert!((score.value() - 0.2).abs() < 1e-9);
+		}
+
+		// The first participant fully distrusts the last one, which cancels
+		// the trust the others give it
+		let mut distrust = vec![0; NUM_NEIGHBOURS];
+		distrust[4] = SCALE;
+		let entries = vec![(pks[0], distrust)];
+		let scores = manager.calculate_scores_with_distrust(&entries, NUM_ITER).unwrap();
+		for score in &scores[..4] {
+			assert!((score.value() - 0.25).abs() < 1e-9);
+		}
+		assert!(scores[4].value() < 1e-9);
+
+		let repeated = vec![entries[0].clone(), entries[0].clone()];
+		let res = manager.calculate_scores_with_distrust(&repeated, NUM_ITER);
+		assert!(matches!(res, Err(EigenError::DuplicateParticipant { .. })));
+		let over_scale = vec![(pks[0], vec![SCALE; NUM_NEIGHBOURS])];
+		let res = manager.calculate_scores_with_distrust(&over_scale, NUM_ITER);
+		assert_eq!(res, Err(EigenError::InvalidScore));
+		let overflowing = vec![(pks[0], vec![u128::MAX; NUM_NEIGHBOURS])];
+		let res = manager.calculate_scores_with_distrust(&overflowing, NUM_ITER);
+		assert_eq!(res, Err(EigenError::InvalidScore));
+		let outsider = SecretKey::random(&mut thread_rng()).public();
+		let res = manager
+			.calculate_scores_with_distrust(&[(outsider, vec![0; NUM_NEIGHBOURS])], NUM_ITER);
+		assert_eq!(res, Err(EigenError::ParticipantNotFound));
+	}
+
+	#[test]
+	fn should_build_coverage_matrix() {
+		let mut manager = Manager::new_native_only();
+		let none = vec![vec![false; NUM_NEIGHBOURS]; NUM_NEIGHBOURS];
+		assert_eq!(manager.coverage_matrix(), none);
+
+		let (sks, pks) = keyset_from_raw(FIXED_SET);
+		let mut scores = vec![Scalar::zero(); NUM_NEIGHBOURS];
+		scores[1] = Scalar::from_u128(SCALE);
+		let (_, messages) =
+			calculate_message_hash::<NUM_NEIGHBOURS, 1>(pks.clone(), vec![scores.clone()]);
+		let sig = sign(&sks[0], &pks[0], messages[0]);
+		manager.add_attestation(Attestation::new(sig, pks[0], pks.clone(), scores)).unwrap();
+
+		let mut expected = none;
+		expected[0][1] = true;
+		assert_eq!(manager.coverage_matrix(), expected);
+	}
+
+	#[test]
+	fn should_clamp_tiny_scores_to_floor() {
+		let mut manager = Manager::new_native_only();
+		let pub_ins = [1, 4999, 0, 0, 0].map(Scalar::from_u128).to_vec();
+		manager.cached_proofs.insert(Epoch(0), Proof { pub_ins, proof: Vec::new() });
+
+		let scores = manager.checked_normalized_scores(Epoch(0)).unwrap();
+		assert_eq!(scores[0].value(), 0.0002);
+
+		manager.set_score_floor(Score::new(0.001).unwrap());
+		let scores = manager.checked_normalized_scores(Epoch(0)).unwrap();
+		let values: Vec<f64> = scores.iter().map(Score::value).collect();
+		assert_eq!(values, vec![0.001, 0.9998, 0.0, 0.0, 0.0]);
+	}
+
+	#[test]
+	fn should_query_public_inputs() {
+		let mut manager = proving_manager(ParamsKZG::new(14));
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+
+		let proof = manager.get_proof(Epoch(0)).unwrap();
+		assert_eq!(manager.public_inputs(Epoch(0)).unwrap(), proof.pub_ins);
+
+		let initial_score_hex = format!("0x{:064x}", INITIAL_SCORE);
+		let hex_pub_ins = manager.public_inputs_hex(Epoch(0)).unwrap();
+		assert_eq!(hex_pub_ins, vec![initial_score_hex; NUM_NEIGHBOURS]);
+
+		let res = manager.public_inputs(Epoch(1));
+		assert_eq!(res.unwrap_err(), EigenError::ProofNotFound {
+			epoch: Some(1)
+		});
+	}
+
+	#[test]
+	fn should_compute_attestation_root() {
+		let mut manager = Manager::new_native_only();
+		let height = NUM_NEIGHBOURS.next_power_of_two().trailing_zeros() as usize;
+		let empty_root = MerkleTree::<Scalar, Params>::build_tree(vec![], height).root();
+		assert_eq!(manager.attestation_root(), empty_root);
+
+		manager.generate_initial_attestations();
+		let root = manager.attestation_root();
+		assert_ne!(root, empty_root);
+		assert_eq!(manager.attestation_root(), root);
+	}
+
+	#[test]
+	fn should_diff_attestations() {
+		let mut manager = Manager::new_native_only();
+		let mut other = Manager::new_native_only();
+		manager.generate_initial_attestations();
+
+		let (sks, pks) = keyset_from_raw(FIXED_SET);
+		let pks_bs58: Vec<String> = pks.iter().map(public_key_to_bs58).collect();
+		let mut sorted_pks = pks_bs58.clone();
+		sorted_pks.sort();
+
+		let diff = manager.diff_attestations(&other);
+		assert_eq!(diff.added, sorted_pks);
+		assert!(diff.removed.is_empty());
+		assert!(diff.modified.is_empty());
+
+		let diff = other.diff_attestations(&manager);
+		assert_eq!(diff.removed, sorted_pks);
+
+		// Replace the attestation of the first participant in the other manager
+		other.generate_initial_attestations();
+		let mut scores = vec![Scalar::zero(); NUM_NEIGHBOURS];
+		scores[1] = Scalar::from_u128(INITIAL_SCORE);
+		let (_, messages) =
+			calculate_message_hash::<NUM_NEIGHBOURS, 1>(pks.clone(), vec![scores.clone()]);
+		let sig = sign(&sks[0], &pks[0], messages[0]);
+		let att = Attestation::new(sig, pks[0], pks.clone(), scores);
+		other.add_attestation(att).unwrap();
+
+		let diff = manager.diff_attestations(&other);
+		let expected =
+			AttestationDiff { added: vec![], removed: vec![], modified: vec![pks_bs58[0].clone()] };
+		assert_eq!(diff, expected);
+	}
+
+	#[test]
+	fn should_clear_state() {
+		let mut manager = Manager::new_native_only();
+		manager.generate_initial_attestations();
+		for epoch in 0..3 {
+			let pub_ins = vec![Scalar::from_u128(INITIAL_SCORE); NUM_NEIGHBOURS];
+			manager.cached_proofs.insert(Epoch(epoch), Proof { pub_ins, proof: Vec::new() });
+		}
+		manager.set_max_cached_epochs(Some(2));
+		manager.record_proving_time(Duration::from_secs(2));
+		assert_eq!(manager.proof_cache_stats(), (2, Some(2), 1));
+
+		assert_eq!(manager.clear(), (NUM_NEIGHBOURS, 2));
+		assert!(manager.attestations.is_empty());
+		assert_eq!(
+			manager.get_last_epoch().unwrap_err(),
+			EigenError::ProofNotFound { epoch: None }
+		);
+		assert_eq!(manager.proof_cache_stats(), (0, Some(2), 0));
+		assert_eq!(manager.estimate_proving_time(), Duration::from_secs(2));
+	}
+
+	#[test]
+	fn should_verify_transcript_with_public_inputs() {
+		let mut manager = proving_manager(ParamsKZG::new(14));
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+
+		let proof = manager.get_proof(Epoch(0)).unwrap();
+		let transcript = proof.transcript_bytes().to_vec();
+		let pub_ins = manager.public_inputs(Epoch(0)).unwrap();
+		let verifier_code = manager.prover.as_ref().unwrap().verifier_code.clone();
+		evm_verify(verifier_code, vec![pub_ins], transcript);
+	}
+
+	#[test]
+	fn should_normalize_scores() {
+		let mut manager = proving_manager(ParamsKZG::new(14));
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+
+		let scores = manager.normalized_scores(Epoch(0)).unwrap();
+		let expected = Score::new(1.0 / NUM_NEIGHBOURS as f64).unwrap();
+		assert_eq!(scores, vec![expected; NUM_NEIGHBOURS]);
+
+		let res = manager.normalized_scores(Epoch(1));
+		assert_eq!(res.unwrap_err(), EigenError::ProofNotFound {
+			epoch: Some(1)
+		});
+	}
+
+	#[test]
+	fn should_normalize_scores_on_log_scale() {
+		let mut manager = proving_manager(ParamsKZG::new(14));
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+		// Uniform scores stay uniform
+		let scores = manager.normalized_scores_log(Epoch(0)).unwrap();
+		for score in scores {
+			assert!((score.value() - 1.0 / NUM_NEIGHBOURS as f64).abs() < 1e-12);
+		}
+
+		// Normalized scores of [0, 0.6, 0.4, 0, 0] map to [0, ln(4), ln(3), 0, 0]
+		let atts = signed_attestations(vec![
+			[0, 0, 1000, 0, 0],
+			[0, 0, 1000, 0, 0],
+			[0, 1000, 0, 0, 0],
+			[0, 1000, 0, 0, 0],
+			[0, 0, 1000, 0, 0],
+		]);
+		for att in atts {
+			manager.add_attestation(att).unwrap();
+		}
+		manager.calculate_proofs(Epoch(1)).unwrap();
+		let scores = manager.normalized_scores_log(Epoch(1)).unwrap();
+		let total = 4f64.ln() + 3f64.ln();
+		let expected = [0.0, 4f64.ln() / total, 3f64.ln() / total, 0.0, 0.0];
+		for (score, expected) in scores.iter().zip(expected) {
+			assert!((score.value() - expected).abs() < 1e-12);
+		}
+	}
+
+	#[test]
+	fn should_export_scores_as_attestation() {
+		let mut manager = proving_manager(ParamsKZG::new(14));
+		let (sks, pks) = keyset_from_raw(FIXED_SET);
+		let res = manager.export_as_attestation(Epoch(0), &sks[0]);
+		assert_eq!(res.unwrap_err(), EigenError::ProofNotFound {
+			epoch: Some(0)
+		});
+
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+		let att = manager.export_as_attestation(Epoch(0), &sks[0]).unwrap();
+		assert_eq!(att.pk, pks[0]);
+		assert_eq!(att.neighbours, pks);
+		let score = Scalar::from_u128(SCALE / NUM_NEIGHBOURS as u128);
+		assert_eq!(att.scores, vec![score; NUM_NEIGHBOURS]);
+
+		// Signed by a participant, so it is accepted by other managers
+		manager.add_attestation(att).unwrap();
+	}
+
+	#[test]
+	fn should_check_normalized_scores_sum() {
+		let mut manager = proving_manager(ParamsKZG::new(14));
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+		let scores = manager.checked_normalized_scores(Epoch(0)).unwrap();
+		assert_eq!(scores, manager.normalized_scores(Epoch(0)).unwrap());
+
+		// Many uneven scores, so the float conversion rounds most of them
+		let pub_ins = (1..=10_000u128).map(|x| Scalar::from_u128(x * x * 7919)).collect();
+		let proof = Proof { pub_ins, proof: Vec::new() };
+		manager.cached_proofs.insert(Epoch(1), proof);
+		let scores = manager.checked_normalized_scores(Epoch(1)).unwrap();
+		assert_eq!(scores.len(), 10_000);
+		let sum: f64 = scores.iter().map(Score::value).sum();
+		assert!((sum - 1.0).abs() <= NORMALIZATION_TOLERANCE);
+	}
+
+	#[test]
+	fn should_validate_proof_bounds() {
+		let max = NUM_NEIGHBOURS as u128 * INITIAL_SCORE;
+		let mut pub_ins = vec![Scalar::zero(); NUM_NEIGHBOURS];
+		pub_ins[0] = Scalar::from_u128(max);
+		let proof = Proof { pub_ins, proof: Vec::new() };
+		assert_eq!(Manager::validate_proof_bounds(&proof), Ok(()));
+
+		let mut above = proof.clone();
+		above.pub_ins[1] = Scalar::from_u128(max + 1);
+		assert_eq!(
+			Manager::validate_proof_bounds(&above),
+			Err(EigenError::InvalidScore)
+		);
+
+		// A negative score wraps around to a large field element
+		let mut negative = proof.clone();
+		negative.pub_ins[1] = -Scalar::one();
+		assert_eq!(
+			Manager::validate_proof_bounds(&negative),
+			Err(EigenError::InvalidScore)
+		);
+
+		let mut missing = proof;
+		missing.pub_ins.pop();
+		assert_eq!(
+			Manager::validate_proof_bounds(&missing),
+			Err(EigenError::InvalidScore)
+		);
+	}
+
+	#[test]
+	fn should_verify_attestation_signature() {
+		let mut att = build_fixed_attestations().remove(0);
+		assert!(Manager::verify_attestation_signature(&att));
+
+		att.scores[0] += Scalar::one();
+		assert!(!Manager::verify_attestation_signature(&att));
+	}
+
+	#[test]
+	fn should_prove_attestations_without_caching() {
+		let manager = proving_manager(ParamsKZG::new(14));
+
+		let mut atts = build_fixed_attestations();
+		let proof = manager.prove_attestations(atts.clone()).unwrap();
+		let expected = vec![Scalar::from_u128(INITIAL_SCORE); NUM_NEIGHBOURS];
+		assert_eq!(proof.pub_ins, expected);
+		assert_eq!(manager.attestation_count(), 0);
+		assert!(manager.cached_proofs.is_empty());
+
+		// Replace the last attestation with a duplicate of the first one
+		atts[NUM_NEIGHBOURS - 1] = atts[0].clone();
+		let res = manager.prove_attestations(atts.clone());
+		let reason = "duplicate attestations from the same participant".to_string();
+		assert_eq!(res.unwrap_err(), EigenError::InvalidAttestation { reason });
+
+		atts.pop();
+		let res = manager.prove_attestations(atts);
+		let reason = format!(
+			"expected {} attestations, got {}",
+			NUM_NEIGHBOURS,
+			NUM_NEIGHBOURS - 1
+		);
+		assert_eq!(res.unwrap_err(), EigenError::InvalidAttestation { reason });
+	}
+
+	#[test]
+	fn should_count_attestations() {
+		let mut manager = Manager::new_native_only();
+		assert_eq!(manager.attestation_count(), 0);
+
+		for att in build_fixed_attestations().into_iter().take(3) {
+			manager.add_attestation(att).unwrap();
+		}
+
+		assert_eq!(manager.attestation_count(), 3);
+	}
+
+	#[test]
+	fn should_not_duplicate_readded_attestations() {
+		let mut manager = Manager::new_native_only();
+		manager.generate_initial_attestations();
+		let ops = manager.opinions().unwrap();
+		let root = manager.attestation_root();
+
+		manager.generate_initial_attestations();
+		for att in build_fixed_attestations() {
+			manager.add_attestation(att).unwrap();
+		}
+		assert_eq!(manager.attestation_count(), NUM_NEIGHBOURS);
+		assert_eq!(manager.opinions().unwrap(), ops);
+		assert_eq!(manager.attestation_root(), root);
+	}
+
+	#[test]
+	fn should_add_attestation_set_atomically() {
+		let mut manager = Manager::new_native_only();
+		let mut atts = build_fixed_attestations();
+		atts[3].scores[0] += Scalar::one();
+
+		let res = manager.add_attestation_set(atts.clone());
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let reason = format!(
+			"attestation 3 from {}: {}",
+			public_key_to_bs58(&pks[3]),
+			EigenError::InvalidAttestation { reason: "invalid signature".to_string() }
+		);
+		assert_eq!(res.unwrap_err(), EigenError::InvalidAttestation { reason });
+		assert_eq!(manager.attestation_count(), 0);
+
+		manager.add_attestation_set(build_fixed_attestations()).unwrap();
+		assert_eq!(manager.attestation_count(), NUM_NEIGHBOURS);
+	}
+
+	#[test]
+	fn should_reject_zero_scores() {
+		let mut manager = Manager::new_native_only();
+
+		let (sks, pks) = keyset_from_raw(FIXED_SET);
+		let scores = vec![Scalar::zero(); NUM_NEIGHBOURS];
+		let (_, messages) =
+			calculate_message_hash::<NUM_NEIGHBOURS, 1>(pks.clone(), vec![scores.clone()]);
+		let sig = sign(&sks[0], &pks[0], messages[0]);
+		let att = Attestation::new(sig, pks[0], pks.clone(), scores);
+
+		let res = manager.add_attestation(att);
+		assert_eq!(res.unwrap_err(), EigenError::InvalidScore);
+		assert_eq!(manager.attestation_count(), 0);
+	}
+
+	fn signed_attestations(scores: Vec<[u128; NUM_NEIGHBOURS]>) -> Vec<Attestation> {
+		let (sks, pks) = keyset_from_raw(FIXED_SET);
+		let scores: Vec<Vec<Scalar>> = scores
+			.into_iter()
+			.map(|row| row.into_iter().map(Scalar::from_u128).collect())
+			.collect();
+		let (_, messages) =
+			calculate_message_hash::<NUM_NEIGHBOURS, NUM_NEIGHBOURS>(pks.clone(), scores.clone());
+
+		let mut attestations = Vec::new();
+		for (((sk, pk), msg), scs) in sks.iter().zip(&pks).zip(messages).zip(scores) {
+			let sig = sign(sk, pk, msg);
+			attestations.push(Attestation::new(sig, *pk, pks.clone(), scs));
+		}
+		attestations
+	}
+
+	#[test]
+	fn should_ignore_attestation_order() {
+		let atts = signed_attestations(vec![
+			[0, 0, 1000, 0, 0],
+			[0, 0, 1000, 0, 0],
+			[0, 1000, 0, 0, 0],
+			[0, 1000, 0, 0, 0],
+			[0, 0, 1000, 0, 0],
+		]);
+		let expected: Vec<Scalar> = [0, 3000, 2000, 0, 0].map(Scalar::from_u128).to_vec();
+
+		for shift in 0..NUM_NEIGHBOURS {
+			let mut permuted = atts.clone();
+			permuted.rotate_left(shift);
+			if shift % 2 == 1 {
+				permuted.reverse();
+			}
+			let mut attestations = HashMap::new();
+			for att in permuted {
+				attestations.insert(hash_public_key(&att.pk), att);
+			}
+			let (_, pub_ins) = Manager::circuit_inputs(&attestations).unwrap();
+			assert_eq!(pub_ins, expected);
+		}
+	}
+
+	#[test]
+	fn should_estimate_convergence_rate() {
+		let mut manager = Manager::new_native_only();
+		manager.generate_initial_attestations();
+		// Uniform attestations converge after the first iteration
+		assert!(manager.convergence_rate_estimate().unwrap() < 1e-9);
+
+		// Half of the trust to itself and half to the next one, which gives
+		// eigenvalues of (1 + w^k) / 2 for the fifth roots of unity w^k
+		let ring = signed_attestations(vec![
+			[500, 500, 0, 0, 0],
+			[0, 500, 500, 0, 0],
+			[0, 0, 500, 500, 0],
+			[0, 0, 0, 500, 500],
+			[500, 0, 0, 0, 500],
+		]);
+		for att in ring {
+			manager.add_attestation(att).unwrap();
+		}
+		let rate = manager.convergence_rate_estimate().unwrap();
+		let expected = (std::f64::consts::PI / 5.0).cos();
+		assert!((rate - expected).abs() < 1e-9);
+
+		// Two groups that don't trust each other
+		let disconnected = signed_attestations(vec![
+			[500, 500, 0, 0, 0],
+			[500, 500, 0, 0, 0],
+			[0, 0, 333, 333, 333],
+			[0, 0, 333, 333, 333],
+			[0, 0, 333, 333, 333],
+		]);
+		for att in disconnected {
+			manager.add_attestation(att).unwrap();
+		}
+		let res = manager.convergence_rate_estimate();
+		assert_eq!(res.unwrap_err(), EigenError::NotConverging);
+	}
+
+	#[test]
+	fn should_calculate_marginal_impact() {
+		let mut manager = Manager::new_native_only();
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let res = manager.marginal_impact(&pks[0]);
+		assert!(matches!(res, Err(EigenError::AttestationNotFound { .. })));
+
+		// The first participant's trust goes to the third one, and keeps
+		// bouncing between the second and the third one
+		let atts = signed_attestations(vec![
+			[0, 0, 1000, 0, 0],
+			[0, 0, 1000, 0, 0],
+			[0, 1000, 0, 0, 0],
+			[0, 1000, 0, 0, 0],
+			[0, 0, 1000, 0, 0],
+		]);
+		for att in atts {
+			manager.add_attestation(att).unwrap();
+		}
+		let impact = manager.marginal_impact(&pks[0]).unwrap();
+		assert_eq!(impact, vec![0.0, 1.0, 0.0, 0.0, 0.0]);
+	}
+
+	#[test]
+	fn should_find_untrusted_participants() {
+		let mut manager = Manager::new_native_only();
+		manager.generate_initial_attestations();
+		assert!(manager.untrusted_participants().is_empty());
+
+		// Nobody gives a score to the first and the last participant
+		let atts = signed_attestations(vec![
+			[0, 0, 1000, 0, 0],
+			[0, 500, 500, 0, 0],
+			[0, 1000, 0, 0, 0],
+			[0, 0, 0, 1000, 0],
+			[0, 0, 1000, 0, 0],
+		]);
+		for att in atts {
+			manager.add_attestation(att).unwrap();
+		}
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let expected = vec![public_key_to_bs58(&pks[0]), public_key_to_bs58(&pks[4])];
+		assert_eq!(manager.untrusted_participants(), expected);
+	}
+
+	#[test]
+	fn should_query_attested_scores() {
+		let mut manager = Manager::new_native_only();
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let res = manager.attested_scores(&pks[0]);
+		let pk = public_key_to_bs58(&pks[0]);
+		assert_eq!(res.unwrap_err(), EigenError::AttestationNotFound { pk });
+
+		let atts = signed_attestations(vec![[0, 100, 100, 200, 600]; NUM_NEIGHBOURS]);
+		manager.add_attestation(atts[0].clone()).unwrap();
+		let scores = manager.attested_scores(&pks[0]).unwrap();
+		let expected: Vec<(PublicKey, f64)> =
+			pks.into_iter().zip([0.0, 0.1, 0.1, 0.2, 0.6]).collect();
+		assert_eq!(scores, expected);
+	}
+
+	#[test]
+	fn should_hash_public_key_like_the_circuit() {
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let group = Manager::participant_group();
+		for (pk, pk_hash) in pks.iter().zip(group.iter()) {
+			let inputs = [pk.0.x, pk.0.y, Scalar::zero(), Scalar::zero(), Scalar::zero()];
+			assert_eq!(
+				hash_public_key(pk),
+				PoseidonNativeHasher::new(inputs).permute()[0]
+			);
+			assert_eq!(hash_public_key(pk), *pk_hash);
+		}
+	}
+
+	#[test]
+	fn should_save_and_load_state() {
+		// Restoring verifies the proofs, so both managers share the params
+		let params = ParamsKZG::new(14);
+		let mut manager = proving_manager(params.clone());
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+		let mut state = Vec::new();
+		manager.save(&mut state).unwrap();
+
+		let mut loaded = proving_manager(params);
+		loaded.load(state.as_slice()).unwrap();
+		assert_eq!(
+			loaded.diff_attestations(&manager),
+			AttestationDiff::default()
+		);
+		let proof = manager.get_proof(Epoch(0)).unwrap();
+		let loaded_proof = loaded.get_proof(Epoch(0)).unwrap();
+		assert_eq!(loaded_proof.pub_ins, proof.pub_ins);
+		assert_eq!(loaded_proof.proof, proof.proof);
+
+		// A broken state leaves the manager as it was
+		let res = loaded.load(&state[..state.len() / 2]);
+		assert_eq!(res.unwrap_err(), EigenError::InvalidState);
+		assert_eq!(loaded.attestation_count(), NUM_NEIGHBOURS);
+	}
+
+	#[test]
+	fn should_evict_imported_proofs_over_limit() {
+		let mut manager = Manager::new_native_only();
+		for epoch in 0..3 {
+			let proof = Proof { pub_ins: vec![Scalar::one(); NUM_NEIGHBOURS], proof: Vec::new() };
+			manager.cached_proofs.insert(Epoch(epoch), proof);
+		}
+		let mut snapshot = Vec::new();
+		manager.export_snapshot(&mut snapshot).unwrap();
+
+		let mut imported = Manager::new_native_only();
+		imported.set_max_cached_epochs(Some(2));
+		imported.import_snapshot(snapshot.as_slice()).unwrap();
+		assert_eq!(imported.proof_cache_stats(), (2, Some(2), 1));
+		assert!(!imported.cached_proofs.contains_key(&Epoch(0)));
+	}
+
+	#[test]
+	fn should_reject_imported_proof_without_every_score() {
+		let mut manager = Manager::new_native_only();
+		let proof = Proof { pub_ins: vec![Scalar::one(); NUM_NEIGHBOURS - 1], proof: Vec::new() };
+		manager.cached_proofs.insert(Epoch(0), proof);
+		let mut snapshot = Vec::new();
+		manager.export_snapshot(&mut snapshot).unwrap();
+
+		let mut imported = Manager::new_native_only();
+		let res = imported.import_snapshot(snapshot.as_slice());
+		assert_eq!(res.unwrap_err(), EigenError::InvalidState);
+		assert_eq!(imported.proof_cache_stats().0, 0);
+
+		// A short proof already in the cache has no score to serve
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let last = &pks[NUM_NEIGHBOURS - 1];
+		let res = manager.get_score(last, Epoch(0));
+		assert_eq!(res.unwrap_err(), EigenError::ParticipantNotFound);
+		let scores = manager.scores_for(&[pks[0], *last], Epoch(0)).unwrap();
+		assert_eq!(scores, vec![Some(Scalar::one()), None]);
+		assert!(manager.get_score_history(last, None, None).unwrap().is_empty());
+	}
+
+	#[test]
+	fn should_export_and_import_snapshot() {
+		// Restoring verifies the proofs, so both managers share the params
+		let params = ParamsKZG::new(14);
+		let mut manager = proving_manager(params.clone());
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+		let mut snapshot = Vec::new();
+		manager.export_snapshot(&mut snapshot).unwrap();
+		assert_eq!(snapshot[0], SNAPSHOT_VERSION);
+
+		let mut imported = proving_manager(params);
+		imported.import_snapshot(snapshot.as_slice()).unwrap();
+		assert_eq!(
+			imported.diff_attestations(&manager),
+			AttestationDiff::default()
+		);
+		let proof = manager.get_proof(Epoch(0)).unwrap();
+		let imported_proof = imported.get_proof(Epoch(0)).unwrap();
+		assert_eq!(imported_proof.pub_ins, proof.pub_ins);
+		assert_eq!(imported_proof.proof, proof.proof);
+
+		// A snapshot of another version leaves the manager as it was
+		imported.clear();
+		snapshot[0] = SNAPSHOT_VERSION + 1;
+		let res = imported.import_snapshot(snapshot.as_slice());
+		assert_eq!(res.unwrap_err(), EigenError::InvalidState);
+		assert_eq!(imported.attestation_count(), 0);
+	}
+
+	#[test]
+	fn should_merge_attestations() {
+		let mut manager = Manager::new_native_only();
+		let atts = fixed_attestations();
+		manager.add_attestation(atts[0].clone()).unwrap();
+		let cached = signed_attestations(vec![[0, 250, 250, 250, 250]; NUM_NEIGHBOURS]);
+		manager.add_attestation(cached[1].clone()).unwrap();
+
+		let mut invalid_att = atts[3].clone();
+		invalid_att.scores[0] += Scalar::one();
+		let others = vec![atts[0].clone(), atts[1].clone(), atts[2].clone(), invalid_att];
+		let report = manager.merge(others);
+
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let reason = "invalid signature".to_string();
+		assert_eq!(report, MergeReport {
+			added: vec![public_key_to_bs58(&pks[2])],
+			unchanged: vec![public_key_to_bs58(&pks[0])],
+			conflicts: vec![public_key_to_bs58(&pks[1])],
+			rejected: vec![EigenError::InvalidAttestation { reason }],
+		});
+		assert_eq!(manager.attestation_count(), 3);
+		assert!(same_attestation(
+			manager.get_attestation(&pks[1]).unwrap(),
+			&cached[1]
+		));
+	}
+
+	#[test]
+	fn should_replace_attestation() {
+		let mut manager = Manager::new_native_only();
+		let att = fixed_attestations().remove(0);
+		assert!(!manager.replace_attestation(att.clone()).unwrap());
+		assert!(manager.replace_attestation(att.clone()).unwrap());
+		assert_eq!(manager.attestation_count(), 1);
+
+		let mut invalid_att = att;
+		invalid_att.scores[0] += Scalar::one();
+		let res = manager.replace_attestation(invalid_att);
+		let reason = "invalid signature".to_string();
+		assert_eq!(res.unwrap_err(), EigenError::InvalidAttestation { reason });
+	}
+
+	#[test]
+	fn should_preview_scores() {
+		let mut manager = proving_manager(ParamsKZG::new(14));
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let pk = public_key_to_bs58(&pks[0]);
+		assert_eq!(
+			manager.calculate_scores(NUM_ITER).unwrap_err(),
+			EigenError::AttestationNotFound { pk }
+		);
+
+		manager.generate_initial_attestations();
+		let scores = manager.calculate_scores(NUM_ITER).unwrap();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+		let pub_ins = manager.public_inputs(Epoch(0)).unwrap();
+		assert_eq!(scores, pub_ins);
+
+		// Iterations are capped at the circuit's count
+		let capped_scores = manager.calculate_scores(NUM_ITER + 5).unwrap();
+		assert_eq!(capped_scores, pub_ins);
+
+		// Uniform attestations are converged after the first iteration
+		let partial_scores = manager.calculate_scores(1).unwrap();
+		assert_eq!(partial_scores, pub_ins);
+	}
+
+	#[test]
+	fn should_reject_wrong_score_sum_in_strict_mode() {
+		let mut manager = Manager::new_native_only();
+		manager.set_strict_score_sum(true);
+		let atts = signed_attestations(vec![[200, 200, 200, 200, 201]; NUM_NEIGHBOURS]);
+		let res = manager.add_attestation(atts[0].clone());
+		assert_eq!(res.unwrap_err(), EigenError::InvalidScoreSum);
+
+		// Wrapping around the field doesn't make up for it
+		let mut wrapped = fixed_attestations().remove(0);
+		wrapped.scores[0] = -Scalar::one();
+		wrapped.scores[1] += Scalar::one();
+		let res = manager.validate_attestation(&wrapped);
+		assert_eq!(res.unwrap_err(), EigenError::InvalidScoreSum);
+
+		manager.generate_initial_attestations();
+		for att in fixed_attestations() {
+			manager.add_attestation(att).unwrap();
+		}
+
+		manager.set_strict_score_sum(false);
+		manager.add_attestation(atts[0].clone()).unwrap();
+	}
+
+	#[test]
+	fn should_conserve_total_score_in_strict_mode() {
+		let total = Scalar::from_u128(INITIAL_SCORE * NUM_NEIGHBOURS as u128);
+		let sum = |scores: &[Scalar]| scores.iter().fold(Scalar::zero(), |acc, s| acc + *s);
+		let mut manager = Manager::new_native_only();
+		manager.set_strict_score_sum(true);
+
+		// Uneven rows, each summing up to `SCALE`
+		let atts = signed_attestations(vec![
+			[0, 700, 300, 0, 0],
+			[100, 0, 0, 0, 900],
+			[333, 333, 0, 334, 0],
+			[0, 0, 1000, 0, 0],
+			[250, 0, 500, 250, 0],
+		]);
+		for att in atts {
+			manager.add_attestation(att).unwrap();
+		}
+		let scores = manager.calculate_scores(NUM_ITER).unwrap();
+		assert_ne!(scores, vec![
+			Scalar::from_u128(INITIAL_SCORE);
+			NUM_NEIGHBOURS
+		]);
+		assert_eq!(sum(&scores), total);
+
+		// A row over `SCALE`, accepted outside strict mode, adds trust
+		manager.set_strict_score_sum(false);
+		let over = signed_attestations(vec![[0, 1000, 1000, 0, 0]; NUM_NEIGHBOURS]);
+		manager.add_attestation(over[0].clone()).unwrap();
+		let scores = manager.calculate_scores(NUM_ITER).unwrap();
+		assert_ne!(sum(&scores), total);
+	}
+
+	#[test]
+	fn should_calculate_score_entropy() {
+		let mut manager = Manager::new_native_only();
+		let pub_ins = |scores: [u128; NUM_NEIGHBOURS]| Proof {
+			pub_ins: scores.into_iter().map(Scalar::from_u128).collect(),
+			proof: Vec::new(),
+		};
+		manager.cached_proofs.insert(Epoch(1), pub_ins([1000; NUM_NEIGHBOURS]));
+		manager.cached_proofs.insert(Epoch(2), pub_ins([2500, 2500, 0, 0, 0]));
+		manager.cached_proofs.insert(Epoch(3), pub_ins([5000, 0, 0, 0, 0]));
+
+		let uniform = manager.score_entropy(Epoch(1)).unwrap();
+		assert!((uniform - (NUM_NEIGHBOURS as f64).log2()).abs() < 1e-12);
+		assert_eq!(manager.score_entropy(Epoch(2)).unwrap(), 1.0);
+		assert_eq!(manager.score_entropy(Epoch(3)).unwrap(), 0.0);
+
+		let res = manager.score_entropy(Epoch(4));
+		assert_eq!(res.unwrap_err(), EigenError::ProofNotFound {
+			epoch: Some(4)
+		});
+	}
+
+	#[test]
+	fn should_diff_rankings() {
+		let mut manager = Manager::new_native_only();
+		let pub_ins = |scores: [u128; NUM_NEIGHBOURS]| Proof {
+			pub_ins: scores.into_iter().map(Scalar::from_u128).collect(),
+			proof: Vec::new(),
+		};
+		manager.cached_proofs.insert(Epoch(1), pub_ins([2000, 1000, 1000, 500, 500]));
+		manager.cached_proofs.insert(Epoch(2), pub_ins([500, 1000, 2000, 500, 1000]));
+
+		let diff = manager.ranking_diff(Epoch(1), Epoch(2)).unwrap();
+		let ranks: Vec<(usize, usize, i64)> =
+			diff.iter().map(|c| (c.rank_a, c.rank_b, c.change)).collect();
+		assert_eq!(ranks, vec![
+			(1, 4, -3),
+			(2, 2, 0),
+			(2, 1, 1),
+			(4, 4, 0),
+			(4, 2, 2)
+		]);
+
+		let res = manager.ranking_diff(Epoch(1), Epoch(3));
+		assert_eq!(res.unwrap_err(), EigenError::ProofNotFound {
+			epoch: Some(3)
+		});
+	}
+
+	#[test]
+	fn should_count_participation() {
+		let mut manager = Manager::new_native_only();
+		assert_eq!(manager.participation(), (0, NUM_NEIGHBOURS));
+
+		for att in fixed_attestations().into_iter().take(3) {
+			manager.add_attestation(att).unwrap();
+		}
+		assert_eq!(manager.participation(), (3, NUM_NEIGHBOURS));
+
+		// Replacing an attestation doesn't count twice
+		manager.generate_initial_attestations();
+		assert_eq!(manager.participation(), (NUM_NEIGHBOURS, NUM_NEIGHBOURS));
+	}
+
+	#[test]
+	fn should_reject_unsupported_signature_scheme() {
+		let mut manager = Manager::new_native_only();
+		let mut att = fixed_attestations().remove(0);
+		att.scheme = SignatureScheme::Unsupported;
+		let res = manager.add_attestation(att);
+		assert_eq!(res.unwrap_err(), EigenError::UnsupportedSignatureScheme);
+		assert!(manager.attestations.is_empty());
+	}
+
+	#[test]
+	fn should_run_exact_iterations() {
+		let mut manager = Manager::new_native_only();
+		for att in signed_attestations(vec![
+			[0, 1000, 0, 0, 0],
+			[0, 0, 1000, 0, 0],
+			[0, 1000, 0, 0, 0],
+			[1000, 0, 0, 0, 0],
+			[1000, 0, 0, 0, 0],
+		]) {
+			manager.add_attestation(att).unwrap();
+		}
+
+		let initial = manager.run_iterations(0).unwrap();
+		assert_eq!(initial, vec![1.0; NUM_NEIGHBOURS]);
+		let first = manager.run_iterations(1).unwrap();
+		assert_eq!(first, vec![2.0, 2.0, 1.0, 0.0, 0.0]);
+
+		// Matches the circuit's truncated result, up to its rounding
+		let circuit = manager.calculate_scores(NUM_ITER).unwrap();
+		let truncated = manager.run_iterations(NUM_ITER).unwrap();
+		for (c, t) in circuit.iter().zip(&truncated) {
+			let c = scalar_to_score(c, SCALE).unwrap();
+			assert!((c - t).abs() < 0.01, "{} != {}", c, t);
+		}
+
+		// The second and the third keep swapping the trust they get, so more
+		// iterations give different scores
+		let longer = manager.run_iterations(NUM_ITER + 1).unwrap();
+		assert_ne!(longer, truncated);
+	}
+
+	#[test]
+	fn should_calculate_scores_without_proving() {
+		let mut manager = Manager::new_native_only();
+		assert!(!manager.is_proving_enabled());
+		manager.generate_initial_attestations();
+
+		let scores = manager.calculate_scores(NUM_ITER).unwrap();
+		assert_eq!(scores, vec![
+			Scalar::from_u128(INITIAL_SCORE);
+			NUM_NEIGHBOURS
+		]);
+
+		let res = manager.calculate_proofs(Epoch(0));
+		assert_eq!(res.unwrap_err(), EigenError::ProvingDisabled);
+		assert!(manager.cached_proofs.is_empty());
+		let res = manager.get_proof(Epoch(0));
+		assert_eq!(res.unwrap_err(), EigenError::ProvingDisabled);
+	}
+
+	#[test]
+	fn should_preview_scores_until_tolerance() {
+		let mut manager = Manager::new_native_only();
+		manager.generate_initial_attestations();
+		let full_scores = manager.calculate_scores(NUM_ITER).unwrap();
+
+		// Uniform attestations don't change the scores at all
+		let (scores, iterations) = manager.calculate_scores_until(0.0).unwrap();
+		assert_eq!(iterations, 1);
+		assert_eq!(scores, full_scores);
+
+		// The first participant's trust keeps bouncing between the second and
+		// the third one, so the scores never settle
+		let atts = signed_attestations(vec![
+			[0, 0, 1000, 0, 0],
+			[0, 0, 1000, 0, 0],
+			[0, 1000, 0, 0, 0],
+			[0, 1000, 0, 0, 0],
+			[0, 0, 1000, 0, 0],
+		]);
+		for att in atts {
+			manager.add_attestation(att).unwrap();
+		}
+		let (scores, iterations) = manager.calculate_scores_until(0.5).unwrap();
+		assert_eq!(iterations, NUM_ITER);
+		assert_eq!(scores, manager.calculate_scores(NUM_ITER).unwrap());
+
+		let (_, iterations) = manager.calculate_scores_until(f64::INFINITY).unwrap();
+		assert_eq!(iterations, 1);
+	}
+
+	#[test]
+	fn should_calculate_personalized_scores() {
+		let mut rng = thread_rng();
+		let mut manager = Manager::new_native_only();
+		manager.generate_initial_attestations();
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+
+		// Before any iteration all the trust is on the seed participant
+		let seed_scores = manager.personalized_scores(&pks[1], 0).unwrap();
+		let mut expected = vec![Scalar::zero(); NUM_NEIGHBOURS];
+		expected[1] = Scalar::from_u128(INITIAL_SCORE * NUM_NEIGHBOURS as u128);
+		assert_eq!(seed_scores, expected);
+
+		// Uniform attestations spread it to the same view as the global scores
+		let scores = manager.personalized_scores(&pks[1], NUM_ITER).unwrap();
+		assert_eq!(scores, manager.calculate_scores(NUM_ITER).unwrap());
+
+		let outsider = SecretKey::random(&mut rng).public();
+		let res = manager.personalized_scores(&outsider, NUM_ITER);
+		assert_eq!(res.unwrap_err(), EigenError::ParticipantNotFound);
+	}
+
+	#[test]
+	fn should_sign_score() {
+		let mut rng = thread_rng();
+		let mut manager = proving_manager(ParamsKZG::new(14));
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let score = manager.get_score(&pks[0], Epoch(0)).unwrap();
+		assert_eq!(score, Scalar::from_u128(INITIAL_SCORE));
+
+		let server_sk = SecretKey::random(&mut rng);
+		let server_pk = server_sk.public();
+		let message_hash = score_message_hash(&pks[0], Epoch(0), score);
+		let sig = sign(&server_sk, &server_pk, message_hash);
+		assert!(verify_sig(&sig, &server_pk, message_hash));
+
+		let other_epoch_hash = score_message_hash(&pks[0], Epoch(1), score);
+		assert!(!verify_sig(&sig, &server_pk, other_epoch_hash));
+	}
+
+	#[cfg(feature = "bench")]
+	#[test]
+	fn should_benchmark_convergence() {
+		let mut manager = proving_manager(ParamsKZG::new(14));
+		manager.generate_initial_attestations();
+		manager.set_max_cached_epochs(Some(1));
+		let (native_time, proving_time) = manager.benchmark_convergence(Epoch(0)).unwrap();
+
+		assert!(native_time < proving_time);
+		assert!(manager.get_proof(Epoch(0)).is_ok());
+		assert_eq!(manager.estimate_proving_time(), proving_time);
+
+		// Cached within the limit, like the proofs of `calculate_proofs`
+		manager.benchmark_convergence(Epoch(1)).unwrap();
+		assert!(manager.get_proof(Epoch(0)).is_err());
+		assert!(manager.get_proof(Epoch(1)).is_ok());
+	}
+
+	#[test]
+	fn should_evict_oldest_proofs() {
+		let mut manager = proving_manager(ParamsKZG::new(14));
+		manager.generate_initial_attestations();
+		manager.set_max_cached_epochs(Some(2));
+		for epoch in [3, 1, 2] {
+			manager.calculate_proofs(Epoch(epoch)).unwrap();
+		}
+		assert_eq!(manager.proof_cache_stats(), (2, Some(2), 1));
+		assert!(manager.get_proof(Epoch(1)).is_err());
+		assert!(manager.get_proof(Epoch(2)).is_ok());
+		assert!(manager.get_proof(Epoch(3)).is_ok());
+
+		manager.set_max_cached_epochs(Some(1));
+		assert_eq!(manager.proof_cache_stats(), (1, Some(1), 2));
+		assert!(manager.get_proof(Epoch(3)).is_ok());
+	}
+
+	#[test]
+	fn should_evict_invalid_proof() {
+		let mut manager = proving_manager(ParamsKZG::new(14));
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+		assert!(manager.get_verified_proof(Epoch(0)).is_ok());
+
+		let proof = manager.cached_proofs.get_mut(&Epoch(0)).unwrap();
+		proof.pub_ins[0] += Scalar::one();
+		let res = manager.get_verified_proof(Epoch(0));
+		assert_eq!(res.unwrap_err(), EigenError::InvalidProof { epoch: 0 });
+		assert!(manager.cached_proofs.is_empty());
+
+		// Once evicted, the proof is missing like any other
+		let res = manager.get_verified_proof(Epoch(0));
+		assert_eq!(res.unwrap_err(), EigenError::ProofNotFound {
+			epoch: Some(0)
+		});
+	}
+
+	#[test]
+	fn should_query_scores_in_order() {
+		let mut rng = thread_rng();
+		let mut manager = proving_manager(ParamsKZG::new(14));
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(0)).unwrap();
+
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let non_member = SecretKey::random(&mut rng).public();
+		let query = [pks[2], non_member, pks[0]];
+		let scores = manager.scores_for(&query, Epoch(0)).unwrap();
+
+		let score = Scalar::from_u128(INITIAL_SCORE);
+		assert_eq!(scores, vec![Some(score), None, Some(score)]);
+
+		let res = manager.scores_for(&query, Epoch(1));
+		assert_eq!(res.unwrap_err(), EigenError::ProofNotFound {
+			epoch: Some(1)
+		});
+	}
+
+	#[test]
+	fn should_query_score_history() {
+		let mut manager = proving_manager(ParamsKZG::new(14));
+		manager.generate_initial_attestations();
+		manager.calculate_proofs(Epoch(1)).unwrap();
+		manager.calculate_proofs(Epoch(3)).unwrap();
+
+		let (_, pks) = keyset_from_raw(FIXED_SET);
+		let history = manager.get_score_history(&pks[0], None, None).unwrap();
+		let score = Scalar::from_u128(INITIAL_SCORE);
+		assert_eq!(history, vec![(Epoch(1), score), (Epoch(3), score)]);
+
+		let history = manager.get_score_history(&pks[0], Some(Epoch(2)), None).unwrap();
+		assert_eq!(history, vec![(Epoch(3), score)]);
+
+		let unknown_pk = PublicKey::default();
+		let res = manager.get_score_history(&unknown_pk, None, None);
+		assert_eq!(res.unwrap_err(), EigenError::ParticipantNotFound);
+	}
 }
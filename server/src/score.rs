@@ -0,0 +1,59 @@
+//! The module for the scores served to clients, like:
+//! - Validating a score on construction
+//! - Displaying and serializing a score
+
+use crate::error::EigenError;
+use serde::Serialize;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// Score of a participant, normalized to the `[0, 1]` range.
+/// Construction fails for values that are not finite or fall outside of the
+/// range, so a degenerate computation can't reach the clients.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Serialize)]
+pub struct Score(f64);
+
+impl Score {
+	/// Constructs the Score, validating the value
+	pub fn new(value: f64) -> Result<Self, EigenError> {
+		if !value.is_finite() || !(0.0..=1.0).contains(&value) {
+			return Err(EigenError::InvalidScore);
+		}
+		Ok(Score(value))
+	}
+
+	/// Returns the inner value
+	pub fn value(&self) -> f64 {
+		self.0
+	}
+}
+
+impl Display for Score {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		write!(f, "{}", self.0)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn should_construct_valid_score() {
+		let score = Score::new(0.25).unwrap();
+		assert_eq!(score.value(), 0.25);
+		assert_eq!(score.to_string(), "0.25");
+		assert_eq!(serde_json::to_string(&score).unwrap(), "0.25");
+
+		assert!(Score::new(0.0).is_ok());
+		assert!(Score::new(1.0).is_ok());
+	}
+
+	#[test]
+	fn should_reject_invalid_score() {
+		assert_eq!(Score::new(f64::NAN), Err(EigenError::InvalidScore));
+		assert_eq!(Score::new(f64::INFINITY), Err(EigenError::InvalidScore));
+		assert_eq!(Score::new(f64::NEG_INFINITY), Err(EigenError::InvalidScore));
+		assert_eq!(Score::new(-0.1), Err(EigenError::InvalidScore));
+		assert_eq!(Score::new(1.5), Err(EigenError::InvalidScore));
+	}
+}
@@ -1,7 +1,11 @@
+use crate::error::EigenError;
 use eigen_trust_circuit::{
 	eddsa::native::{PublicKey, SecretKey},
+	edwards::native::Point,
 	halo2::halo2curves::{bn256::Fr as Scalar, FieldExt},
+	utils::to_short,
 };
+use rand::rngs::OsRng;
 
 /// Write an array of 32 elements into an array of 64 elements.
 pub fn to_wide(p: [u8; 32]) -> [u8; 64] {
@@ -23,6 +27,120 @@ pub fn scalar_from_bs58(key: &str) -> Scalar {
 	Scalar::from_bytes_wide(&to_wide_bytes(bytes))
 }
 
+/// Encode a scalar as a big-endian 32 byte word, matching its `uint256` and
+/// `bytes32` encodings on-chain
+pub(crate) fn scalar_to_word(s: &Scalar) -> [u8; 32] {
+	let mut word = s.to_bytes();
+	word.reverse();
+	word
+}
+
+/// Encode a scalar as a `0x`-prefixed big-endian hex string, matching its
+/// `uint256` encoding on-chain
+pub fn scalar_to_hex(s: &Scalar) -> String {
+	let hex: String = s.to_bytes().iter().rev().map(|b| format!("{:02x}", b)).collect();
+	format!("0x{}", hex)
+}
+
+/// Encode bytes as a `0x`-prefixed hex string, in the order they are given
+pub fn bytes_to_hex(bytes: &[u8]) -> String {
+	let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+	format!("0x{}", hex)
+}
+
+/// Decode a `0x`-prefixed hex string into bytes. Returns `None` if the prefix
+/// is missing or the string is not valid hex.
+pub fn bytes_from_hex(hex: &str) -> Option<Vec<u8>> {
+	let hex = hex.strip_prefix("0x")?;
+	if hex.len() % 2 != 0 || !hex.is_ascii() {
+		return None;
+	}
+	(0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok()).collect()
+}
+
+/// Convert a scalar to `u128`. Returns `None` if it doesn't fit.
+pub fn scalar_to_u128(s: &Scalar) -> Option<u128> {
+	let bytes = s.to_bytes();
+	if bytes[16..].iter().any(|b| *b != 0) {
+		return None;
+	}
+	let mut low = [0u8; 16];
+	low.copy_from_slice(&bytes[..16]);
+	Some(u128::from_le_bytes(low))
+}
+
+/// Convert a score scaled by `scale` into its `f64` value. Fails if the scalar
+/// doesn't fit into `u128`, or if the scale is zero. Values above 2^53 are
+/// rounded to the nearest `f64`, so the relative error is at most 2^-53.
+pub fn scalar_to_score(s: &Scalar, scale: u128) -> Result<f64, EigenError> {
+	let value = scalar_to_u128(s).ok_or(EigenError::InvalidScore)?;
+	if scale == 0 {
+		return Err(EigenError::InvalidScore);
+	}
+	Ok(value as f64 / scale as f64)
+}
+
+/// Construct a public key from the bs58 encoding of its raw coordinates.
+/// Returns `None` if the string is not a valid encoding of a point.
+pub fn public_key_from_bs58(key: &str) -> Option<PublicKey> {
+	let bytes = bs58::decode(key).into_vec().ok()?;
+	if bytes.len() != 64 {
+		return None;
+	}
+	let x: Option<Scalar> = Scalar::from_bytes(&to_short(&bytes[..32])).into();
+	let y: Option<Scalar> = Scalar::from_bytes(&to_short(&bytes[32..])).into();
+	Some(PublicKey(Point::new(x?, y?)))
+}
+
+/// Construct a public key from the `0x`-prefixed hex encoding of its raw
+/// coordinates. Returns `None` if the string is not a valid encoding of a
+/// point.
+pub fn public_key_from_hex(key: &str) -> Option<PublicKey> {
+	let bytes = bytes_from_hex(key)?;
+	if bytes.len() != 64 {
+		return None;
+	}
+	let x: Option<Scalar> = Scalar::from_bytes(&to_short(&bytes[..32])).into();
+	let y: Option<Scalar> = Scalar::from_bytes(&to_short(&bytes[32..])).into();
+	Some(PublicKey(Point::new(x?, y?)))
+}
+
+/// Construct a public key from either encoding of its raw coordinates, hex
+/// when the string starts with `0x`, and bs58 otherwise
+pub fn public_key_from_str(key: &str) -> Option<PublicKey> {
+	if key.starts_with("0x") {
+		public_key_from_hex(key)
+	} else {
+		public_key_from_bs58(key)
+	}
+}
+
+/// Encode the raw coordinates of a public key as a bs58 string
+pub fn public_key_to_bs58(pk: &PublicKey) -> String {
+	let [x, y] = pk.to_raw();
+	bs58::encode([x, y].concat()).into_string()
+}
+
+/// Generate a new keypair from the OS random number generator. Returns the
+/// secret key, the public key, and their bs58 encodings, with the two parts
+/// of the secret key encoded separately like in `FIXED_SET`.
+pub fn generate_keypair() -> (SecretKey, PublicKey, [String; 2], String) {
+	let sk = SecretKey::random(&mut OsRng);
+	let pk = sk.public();
+	let sk_bs58 = sk.to_raw().map(|part| bs58::encode(part).into_string());
+	let pk_bs58 = public_key_to_bs58(&pk);
+	(sk, pk, sk_bs58, pk_bs58)
+}
+
+/// Construct a secret key from the bs58 encodings of its two parts, separated
+/// by a comma. Returns `None` if either part is not a valid 32 byte encoding.
+pub fn secret_key_from_bs58(key: &str) -> Option<SecretKey> {
+	let (sk0, sk1) = key.split_once(',')?;
+	let sk0_bytes: [u8; 32] = bs58::decode(sk0.trim()).into_vec().ok()?.try_into().ok()?;
+	let sk1_bytes: [u8; 32] = bs58::decode(sk1.trim()).into_vec().ok()?.try_into().ok()?;
+	Some(SecretKey::from_raw([sk0_bytes, sk1_bytes]))
+}
+
 /// Construct the secret keys and public keys from the given raw data
 pub fn keyset_from_raw<const N: usize>(
 	sks_raw: [[&str; 2]; N],
@@ -48,3 +166,84 @@ pub fn keyset_from_raw<const N: usize>(
 
 	(sks, pks)
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::manager::{INITIAL_SCORE, SCALE};
+
+	#[test]
+	fn should_convert_scalar_to_score() {
+		let initial_score = Scalar::from_u128(INITIAL_SCORE);
+		assert_eq!(scalar_to_score(&initial_score, SCALE).unwrap(), 1.0);
+
+		let below = Scalar::from_u128(INITIAL_SCORE - 1);
+		assert_eq!(scalar_to_score(&below, SCALE).unwrap(), 0.999);
+
+		let above = Scalar::from_u128(INITIAL_SCORE + 1);
+		assert_eq!(scalar_to_score(&above, SCALE).unwrap(), 1.001);
+
+		assert_eq!(scalar_to_score(&Scalar::zero(), SCALE).unwrap(), 0.0);
+
+		// Beyond the exact integer range of f64, rounded to the nearest value
+		let large = Scalar::from_u128((1 << 53) + 1);
+		assert_eq!(scalar_to_score(&large, 1).unwrap(), 9007199254740992.0);
+	}
+
+	#[test]
+	fn should_fail_scalar_to_score() {
+		let initial_score = Scalar::from_u128(INITIAL_SCORE);
+		assert_eq!(
+			scalar_to_score(&initial_score, 0),
+			Err(EigenError::InvalidScore)
+		);
+
+		let too_large = -Scalar::one();
+		assert_eq!(
+			scalar_to_score(&too_large, SCALE),
+			Err(EigenError::InvalidScore)
+		);
+	}
+
+	#[test]
+	fn should_parse_public_key_from_hex_and_bs58() {
+		let (_, pks) = keyset_from_raw(crate::manager::FIXED_SET);
+		let [x, y] = pks[0].to_raw();
+		let bs58_key = public_key_to_bs58(&pks[0]);
+		let hex_key = bytes_to_hex(&[x, y].concat());
+
+		let from_bs58 = public_key_from_str(&bs58_key).unwrap();
+		let from_hex = public_key_from_str(&hex_key).unwrap();
+		assert_eq!(from_bs58, pks[0]);
+		assert_eq!(from_hex, pks[0]);
+
+		assert_eq!(public_key_from_str("0x1234"), None);
+		let bad_hex = format!("{}zz", &hex_key[..hex_key.len() - 2]);
+		assert_eq!(public_key_from_str(&bad_hex), None);
+		assert_eq!(public_key_from_str("not-bs58"), None);
+	}
+
+	#[test]
+	fn should_generate_keypair() {
+		let (sk, pk, sk_bs58, pk_bs58) = generate_keypair();
+		assert_eq!(sk.public(), pk);
+		assert_eq!(public_key_from_bs58(&pk_bs58), Some(pk));
+
+		let (_, pks) = keyset_from_raw([[sk_bs58[0].as_str(), sk_bs58[1].as_str()]]);
+		assert_eq!(pks[0], pk);
+
+		let (_, other_pk, ..) = generate_keypair();
+		assert_ne!(other_pk, pk);
+	}
+
+	#[test]
+	fn should_parse_secret_key_from_bs58() {
+		let (_, pk, sk_bs58, _) = generate_keypair();
+		let sk = secret_key_from_bs58(&sk_bs58.join(",")).unwrap();
+		assert_eq!(sk.public(), pk);
+
+		assert!(secret_key_from_bs58(&sk_bs58[0]).is_none());
+		assert!(secret_key_from_bs58(&format!("{},not-bs58", sk_bs58[0])).is_none());
+		assert!(secret_key_from_bs58(&format!("{},{}", sk_bs58[0], "2g")).is_none());
+	}
+}